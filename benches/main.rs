@@ -1,3 +1,5 @@
+use std::time::{Duration, Instant};
+
 use bumpalo::Bump;
 use criterion::{black_box, criterion_group, criterion_main, BenchmarkId, Criterion, Throughput};
 
@@ -13,11 +15,6 @@ impl<T> Drop for ToDrop<T> {
 }
 
 fn comparison(c: &mut Criterion) {
-    assert_eq!(
-        rodeo::HEADER_LAYOUT.size(),
-        std::mem::size_of::<(usize, usize)>()
-    );
-
     let mut group = c.benchmark_group("typed_vs_rodeo");
     let block = 500_usize;
     for i in 0..10 {
@@ -75,7 +72,7 @@ fn with_rodeo_no_drop_need_drop(n: usize) {
     for i in 0..n {
         arena.alloc(ToDrop(i));
     }
-    let _ = arena.into_allocator();
+    let _ = arena.into_allocator_leaking();
 }
 
 fn with_rodeo_no_drop(n: usize) {
@@ -86,5 +83,113 @@ fn with_rodeo_no_drop(n: usize) {
     let _ = black_box(arena);
 }
 
-criterion_group!(benches, comparison);
+fn capacity(c: &mut Criterion) {
+    let mut group = c.benchmark_group("with_capacity");
+    let n = 10_000_usize;
+    group.throughput(Throughput::Elements(n as u64));
+    group.bench_function("new", |b| {
+        b.iter(|| {
+            let arena = Rodeo::new();
+            for i in 0..n {
+                arena.alloc(ToDrop(i));
+            }
+            black_box(arena)
+        })
+    });
+    group.bench_function("with_capacity", |b| {
+        b.iter(|| {
+            let arena = rodeo::bumpalo::Rodeo::with_capacity(n * 32);
+            for i in 0..n {
+                arena.alloc(ToDrop(i));
+            }
+            black_box(arena)
+        })
+    });
+    group.finish();
+}
+
+/// Number of allocations after which the arena is replaced, outside of the
+/// measurement, so that memory use stays bounded.
+const LATENCY_ARENA_SIZE: u64 = 10_000;
+
+/// Histogram of individual allocation latencies, by power-of-two buckets of
+/// nanoseconds.
+struct Histogram([u64; 64]);
+
+impl Histogram {
+    fn new() -> Self {
+        Self([0; 64])
+    }
+
+    fn record(&mut self, iters: u64, mut f: impl FnMut(&Rodeo<Bump>, usize)) -> Duration {
+        let mut total = Duration::ZERO;
+        let mut arena = Rodeo::new();
+        for i in 0..iters {
+            if i % LATENCY_ARENA_SIZE == 0 {
+                arena = Rodeo::new();
+            }
+            let start = Instant::now();
+            f(&arena, i as usize);
+            let elapsed = start.elapsed();
+            total += elapsed;
+            let nanos = elapsed.as_nanos().max(1);
+            self.0[nanos.ilog2() as usize] += 1;
+        }
+        black_box(arena);
+        total
+    }
+
+    /// Return an upper bound of the given percentile, in nanoseconds.
+    fn percentile(&self, p: f64) -> u128 {
+        let count: u64 = self.0.iter().sum();
+        let threshold = (count as f64 * p).ceil() as u64;
+        let mut seen = 0;
+        for (i, n) in self.0.iter().enumerate() {
+            seen += n;
+            if seen >= threshold {
+                return 1 << (i + 1);
+            }
+        }
+        u128::MAX
+    }
+
+    fn report(&self, name: &str) {
+        let max = self
+            .0
+            .iter()
+            .rposition(|&n| n > 0)
+            .map_or(0, |i| 1_u128 << (i + 1));
+        eprintln!(
+            "{name}: p50 < {}ns, p99 < {}ns, p99.9 < {}ns, max < {max}ns",
+            self.percentile(0.5),
+            self.percentile(0.99),
+            self.percentile(0.999),
+        );
+    }
+}
+
+fn latency(c: &mut Criterion) {
+    let mut group = c.benchmark_group("alloc_latency");
+    let mut drop_histogram = Histogram::new();
+    let mut no_drop_histogram = Histogram::new();
+    group.bench_function("rodeo", |b| {
+        b.iter_custom(|iters| {
+            drop_histogram.record(iters, |arena, i| {
+                arena.alloc(ToDrop(i));
+            })
+        })
+    });
+    group.bench_function("rodeo_no_drop", |b| {
+        b.iter_custom(|iters| {
+            no_drop_histogram.record(iters, |arena, i| {
+                arena.alloc(i);
+            })
+        })
+    });
+    group.finish();
+    drop_histogram.report("alloc_latency/rodeo");
+    no_drop_histogram.report("alloc_latency/rodeo_no_drop");
+}
+
+criterion_group!(benches, comparison, capacity, latency);
 criterion_main!(benches);
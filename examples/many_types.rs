@@ -0,0 +1,58 @@
+//! Allocate 50 distinct droppable types, to measure the code size of the
+//! per-type finalizers.
+//!
+//! Build it in release mode and list the finalizer instances that survive in
+//! the binary, along with the size of the `.text` section:
+//!
+//! ```shell
+//! cargo build --release --example many_types
+//! nm -C --size-sort target/release/examples/many_types | grep drop_finalizer
+//! size -A target/release/examples/many_types | grep '^\.text'
+//! ```
+//!
+//! With rustc 1.95.0 on `x86_64-unknown-linux-gnu`, the 50 instances of
+//! `drop_finalizer` take 38 bytes each (the drop glue of each type is inlined
+//! into them), the identical slice finalizers are folded into a single 95-byte one, for
+//! about 2 KB out of a `.text` of 258 KB. The allocation paths themselves
+//! (`alloc` and `alloc_slice_fill_with`) take 44 KB.
+//!
+//! Type-erasing the finalizers would not shrink this: the header would store
+//! a pointer to `drop_in_place::<T>` instead, which still needs one instance
+//! per type, of about the same size.
+
+use std::hint::black_box;
+
+use rodeo::{ArenaAlloc, Rodeo};
+
+macro_rules! droppable_types {
+    ($($name:ident),*) => {
+        $(
+            struct $name(String);
+
+            impl Drop for $name {
+                fn drop(&mut self) {
+                    black_box(&self.0);
+                }
+            }
+        )*
+
+        fn alloc_all<A: ArenaAlloc>(rodeo: &Rodeo<A>) {
+            $(
+                let _ = rodeo.alloc($name(String::from(stringify!($name))));
+                let _ = rodeo.alloc_slice_fill_with(2, |_| $name(String::new()));
+            )*
+        }
+    };
+}
+
+droppable_types!(
+    T00, T01, T02, T03, T04, T05, T06, T07, T08, T09, T10, T11, T12, T13, T14, T15, T16, T17, T18,
+    T19, T20, T21, T22, T23, T24, T25, T26, T27, T28, T29, T30, T31, T32, T33, T34, T35, T36, T37,
+    T38, T39, T40, T41, T42, T43, T44, T45, T46, T47, T48, T49
+);
+
+fn main() {
+    let rodeo = Rodeo::new();
+    alloc_all(black_box(&rodeo));
+    println!("{} pending finalizers", rodeo.num_pending_finalizers());
+}
@@ -0,0 +1,39 @@
+//! Support for the unstable `allocator_api` (nightly only).
+
+use core::alloc::{AllocError, Allocator, Layout};
+use core::ptr::NonNull;
+
+use crate::{ArenaAlloc, Rodeo};
+
+/// A shared reference to a `Rodeo` can back standard collections, e.g.
+/// `Vec<T, &Rodeo>`.
+///
+/// The collections own their elements: the elements are dropped by the
+/// collections themselves, never by the arena. Deallocation does nothing,
+/// the memory is only reclaimed with the arena. As such, growing a collection
+/// allocates a new buffer and copies its contents, abandoning the previous
+/// buffer to the arena.
+///
+/// # Example
+///
+/// ```rust
+/// #![feature(allocator_api)]
+///
+/// use rodeo::Rodeo;
+///
+/// let rodeo = Rodeo::new();
+/// let mut v = Vec::new_in(&rodeo);
+/// v.extend_from_slice(&[1, 2, 3]);
+/// assert_eq!(v, [1, 2, 3]);
+/// ```
+unsafe impl<A> Allocator for &Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = self.try_alloc_layout(layout).map_err(|_| AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+}
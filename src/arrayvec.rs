@@ -0,0 +1,61 @@
+//! Support for `arrayvec` collections.
+
+use ::arrayvec::ArrayVec;
+
+use crate::{ArenaAlloc, Rodeo, RodeoError};
+
+impl<A> Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Move the elements of an [`ArrayVec`] into a slice allocated in this
+    /// `Rodeo` and return an exclusive reference to it.
+    ///
+    /// The elements are only dropped by the arena.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use arrayvec::ArrayVec;
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let mut stack = ArrayVec::<String, 4>::new();
+    /// stack.push("parked".to_string());
+    /// let slice = rodeo.alloc_from_arrayvec(stack);
+    /// assert_eq!(slice, ["parked"]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_from_arrayvec<T: 'static, const N: usize>(
+        &self,
+        array: ArrayVec<T, N>,
+    ) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_from_arrayvec(array) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Try to move the elements of an [`ArrayVec`] into a slice allocated in
+    /// this `Rodeo` and return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails. The `ArrayVec` is then
+    /// dropped, along with its elements.
+    pub fn try_alloc_from_arrayvec<T: 'static, const N: usize>(
+        &self,
+        mut array: ArrayVec<T, N>,
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        let src = array.as_ptr();
+        // moving out cannot panic, and nothing is moved if the allocation fails
+        let slice = self.try_alloc_slice_with(array.len(), |i| unsafe { src.add(i).read() })?;
+        unsafe { array.set_len(0) };
+        Ok(slice)
+    }
+}
@@ -0,0 +1,470 @@
+//! Incremental building of strings and slices on top of Rodeo.
+
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::fmt;
+use core::marker::PhantomData;
+use core::ops::Range;
+use core::ptr::NonNull;
+use core::{mem, ptr, slice, str};
+
+use crate::{slice_drop_finalizer, ArenaAlloc, Header, Rodeo, RodeoError};
+
+/// Minimum capacity of the buffer, once something is pushed.
+const MIN_CAPACITY: usize = 64;
+
+/// Growable byte buffer allocated in a [`Rodeo`].
+///
+/// The arena cannot reallocate in place: growing allocates a new buffer and
+/// abandons the previous one to the arena.
+struct RawBuf {
+    ptr: NonNull<u8>,
+    len: usize,
+    capacity: usize,
+}
+
+impl RawBuf {
+    const fn new() -> Self {
+        Self {
+            ptr: NonNull::dangling(),
+            len: 0,
+            capacity: 0,
+        }
+    }
+
+    fn try_extend<A: ArenaAlloc>(
+        &mut self,
+        rodeo: &Rodeo<A>,
+        bytes: &[u8],
+    ) -> Result<(), RodeoError<A::Error>> {
+        let needed = self
+            .len
+            .checked_add(bytes.len())
+            .ok_or(RodeoError::LayoutOverflow)?;
+        if needed > self.capacity {
+            let capacity = needed
+                .max(self.capacity.saturating_mul(2))
+                .max(MIN_CAPACITY);
+            let layout = Layout::array::<u8>(capacity).map_err(|_| RodeoError::LayoutOverflow)?;
+            let new_ptr = rodeo.alloc_layout(layout)?;
+            unsafe { ptr::copy_nonoverlapping(self.ptr.as_ptr(), new_ptr.as_ptr(), self.len) };
+            self.ptr = new_ptr;
+            self.capacity = capacity;
+        }
+        unsafe {
+            ptr::copy_nonoverlapping(bytes.as_ptr(), self.ptr.as_ptr().add(self.len), bytes.len());
+        }
+        self.len = needed;
+        Ok(())
+    }
+}
+
+/// Builder of several strings sharing a single arena allocation.
+///
+/// Strings pushed into the builder are laid out back to back in one buffer,
+/// which is better for locality than as many separate allocations. Once done,
+/// [`StringBuilder::finish`] returns the combined buffer and the range of each
+/// string within it.
+///
+/// # Example
+///
+/// ```rust
+/// use rodeo::Rodeo;
+///
+/// let rodeo = Rodeo::new();
+/// let mut builder = rodeo.string_builder();
+/// let hello = builder.push("hello");
+/// let world = builder.push("world");
+/// let (buffer, ranges) = builder.finish();
+/// assert_eq!(buffer, "helloworld");
+/// assert_eq!(&buffer[ranges[hello].clone()], "hello");
+/// assert_eq!(&buffer[ranges[world].clone()], "world");
+/// ```
+pub struct StringBuilder<'r, A: ArenaAlloc> {
+    rodeo: &'r Rodeo<A>,
+    buf: RawBuf,
+    ranges: Vec<Range<usize>>,
+}
+
+impl<'r, A> StringBuilder<'r, A>
+where
+    A: ArenaAlloc,
+{
+    /// Push a string at the end of the buffer and return its index in the
+    /// range table.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the buffer fails.
+    pub fn push(&mut self, s: &str) -> usize {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(index) = self.try_push(s) {
+            index
+        } else {
+            self.rodeo.out_of_memory();
+        }
+    }
+
+    /// Try to push a string at the end of the buffer and return its index in
+    /// the range table.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the buffer fails.
+    pub fn try_push(&mut self, s: &str) -> Result<usize, RodeoError<A::Error>> {
+        let start = self.buf.len;
+        self.buf.try_extend(self.rodeo, s.as_bytes())?;
+        self.ranges.push(start..self.buf.len);
+        Ok(self.ranges.len() - 1)
+    }
+
+    /// Return the current contents of the buffer.
+    #[must_use]
+    pub const fn as_str(&self) -> &str {
+        unsafe {
+            str::from_utf8_unchecked(slice::from_raw_parts(self.buf.ptr.as_ptr(), self.buf.len))
+        }
+    }
+
+    /// Return the number of pushed strings.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.ranges.len()
+    }
+
+    /// Return `true` if no string was pushed yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.ranges.is_empty()
+    }
+
+    /// Return the combined buffer and the range of each pushed string within
+    /// it, in push order.
+    #[must_use]
+    pub fn finish(self) -> (&'r mut str, Vec<Range<usize>>) {
+        let bytes = unsafe { slice::from_raw_parts_mut(self.buf.ptr.as_ptr(), self.buf.len) };
+        (unsafe { str::from_utf8_unchecked_mut(bytes) }, self.ranges)
+    }
+}
+
+/// Incremental writer of a string allocated in a [`Rodeo`], by pushing
+/// strings and characters or through [`fmt::Write`].
+///
+/// The string is accumulated in a single arena buffer. When the buffer is
+/// full, a larger one is allocated and the previous one is abandoned to the
+/// arena: this intermediate memory is only reclaimed when the arena is
+/// dropped. Use [`Rodeo::alloc_fmt`] instead to format in one go without
+/// any intermediate buffer.
+///
+/// # Example
+///
+/// ```rust
+/// use std::fmt::Write;
+///
+/// use rodeo::Rodeo;
+///
+/// let rodeo = Rodeo::new();
+/// let mut w = rodeo.writer();
+/// for i in 0..3 {
+///     write!(w, "{i},").unwrap();
+/// }
+/// w.push_str("and");
+/// w.push('…');
+/// assert_eq!(w.into_str(), "0,1,2,and…");
+/// ```
+pub struct Writer<'r, A: ArenaAlloc> {
+    rodeo: &'r Rodeo<A>,
+    buf: RawBuf,
+}
+
+impl<'r, A> Writer<'r, A>
+where
+    A: ArenaAlloc,
+{
+    /// Append a string.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the buffer fails.
+    pub fn push_str(&mut self, s: &str) {
+        if self.try_push_str(s).is_err() {
+            self.rodeo.out_of_memory();
+        }
+    }
+
+    /// Try to append a string.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the buffer fails.
+    pub fn try_push_str(&mut self, s: &str) -> Result<(), RodeoError<A::Error>> {
+        self.buf.try_extend(self.rodeo, s.as_bytes())
+    }
+
+    /// Append a character.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the buffer fails.
+    pub fn push(&mut self, c: char) {
+        self.push_str(c.encode_utf8(&mut [0; 4]));
+    }
+
+    /// Try to append a character.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the buffer fails.
+    pub fn try_push(&mut self, c: char) -> Result<(), RodeoError<A::Error>> {
+        self.try_push_str(c.encode_utf8(&mut [0; 4]))
+    }
+
+    /// Return the string written so far.
+    #[must_use]
+    pub const fn as_str(&self) -> &str {
+        unsafe {
+            str::from_utf8_unchecked(slice::from_raw_parts(self.buf.ptr.as_ptr(), self.buf.len))
+        }
+    }
+
+    /// Return the written string.
+    #[must_use]
+    pub fn into_str(self) -> &'r mut str {
+        let bytes = unsafe { slice::from_raw_parts_mut(self.buf.ptr.as_ptr(), self.buf.len) };
+        unsafe { str::from_utf8_unchecked_mut(bytes) }
+    }
+}
+
+impl<A> fmt::Write for Writer<'_, A>
+where
+    A: ArenaAlloc,
+{
+    /// Append a string.
+    ///
+    /// Fails if reserving space for the buffer fails.
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.try_push_str(s).map_err(|_| fmt::Error)
+    }
+}
+
+/// Minimum capacity of a [`SliceBuilder`], once something is pushed.
+const MIN_SLICE_CAPACITY: usize = 4;
+
+/// Builder of a slice whose length is not known upfront.
+///
+/// Elements are pushed into a buffer allocated in the arena, which is
+/// reallocated when full. The allocator may place successive allocations
+/// anywhere (_bumpalo_ for instance allocates downward), so growing moves
+/// the elements and abandons the previous buffer to the arena, like a `Vec`
+/// would. Interleaving other allocations is fine.
+///
+/// This is the arena counterpart of a `Vec`, also growable through
+/// [`Extend`]. The resulting slice has a single finalizer, registered by
+/// [`SliceBuilder::finish`], which consumes the builder without running its
+/// own drop: the elements are then only dropped by the arena, exactly once.
+/// If the builder is dropped instead, the elements pushed so far are dropped
+/// right away, and the arena never sees them.
+///
+/// # Example
+///
+/// ```rust
+/// use rodeo::Rodeo;
+///
+/// let rodeo = Rodeo::new();
+/// let mut builder = rodeo.slice_builder();
+/// for word in "no length known upfront".split(' ') {
+///     builder.push(word.to_string());
+/// }
+/// let words = builder.finish();
+/// assert_eq!(words, ["no", "length", "known", "upfront"]);
+/// ```
+pub struct SliceBuilder<'r, T, A: ArenaAlloc> {
+    rodeo: &'r Rodeo<A>,
+    /// Reserved header of the current buffer, along with its length slot
+    header: Option<(NonNull<Header>, NonNull<usize>)>,
+    ptr: NonNull<T>,
+    len: usize,
+    capacity: usize,
+    marker: PhantomData<T>,
+}
+
+impl<'r, T, A> SliceBuilder<'r, T, A>
+where
+    A: ArenaAlloc,
+{
+    /// Push an element at the end of the slice.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn push(&mut self, value: T) {
+        if self.try_push(value).is_err() {
+            self.rodeo.out_of_memory();
+        }
+    }
+
+    /// Try to push an element at the end of the slice.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails. The element is then
+    /// dropped.
+    pub fn try_push(&mut self, value: T) -> Result<(), RodeoError<A::Error>> {
+        if self.len == self.capacity {
+            self.try_grow()?;
+        }
+        unsafe { self.ptr.as_ptr().add(self.len).write(value) };
+        self.len += 1;
+        Ok(())
+    }
+
+    /// Return the elements pushed so far.
+    #[must_use]
+    pub const fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Return the elements pushed so far, as an exclusive slice.
+    #[must_use]
+    pub fn as_mut_slice(&mut self) -> &mut [T] {
+        unsafe { slice::from_raw_parts_mut(self.ptr.as_ptr(), self.len) }
+    }
+
+    /// Return the number of elements pushed so far.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if no element was pushed yet.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Register the finalizer of the slice, if needed, and return an exclusive
+    /// reference to it.
+    #[must_use]
+    pub fn finish(self) -> &'r mut [T] {
+        let this = mem::ManuallyDrop::new(self);
+        if this.len == 0 {
+            return unsafe { slice::from_raw_parts_mut(NonNull::dangling().as_ptr(), 0) };
+        }
+
+        if let Some((header, len_ptr)) = this.header {
+            unsafe {
+                len_ptr.as_ptr().write(this.len);
+                (*header.as_ptr()).data_layout = Layout::array::<T>(this.len)
+                    .unwrap_or_else(|_| unreachable!("smaller than the capacity"));
+            }
+            this.rodeo.link(header);
+        }
+
+        unsafe { slice::from_raw_parts_mut(this.ptr.as_ptr(), this.len) }
+    }
+
+    /// Return the built slice, as [`SliceBuilder::finish`].
+    ///
+    /// The elements are handed over to the arena, which drops them once: the
+    /// builder itself does not drop them anymore.
+    #[must_use]
+    pub fn into_slice(self) -> &'r mut [T] {
+        self.finish()
+    }
+
+    #[cold]
+    fn try_grow(&mut self) -> Result<(), RodeoError<A::Error>> {
+        let capacity = self
+            .capacity
+            .checked_mul(2)
+            .ok_or(RodeoError::LayoutOverflow)?
+            .max(MIN_SLICE_CAPACITY);
+        let layout = Layout::array::<T>(capacity).map_err(|_| RodeoError::LayoutOverflow)?;
+
+        let (header, ptr) = if mem::needs_drop::<T>() {
+            let (header, len_ptr, ptr) = self.rodeo.reserve_header(
+                None,
+                layout,
+                slice_drop_finalizer::<T>,
+                Layout::new::<usize>(),
+            )?;
+            // the length slot is aligned for `usize` by the reservation
+            #[allow(clippy::cast_ptr_alignment)]
+            let len_ptr = unsafe { NonNull::new_unchecked(len_ptr.cast::<usize>()) };
+            (Some((header, len_ptr)), ptr)
+        } else {
+            (None, self.rodeo.alloc_layout(layout)?.as_ptr())
+        };
+        let ptr = unsafe { NonNull::new_unchecked(ptr.cast::<T>()) };
+
+        unsafe { ptr::copy_nonoverlapping(self.ptr.as_ptr(), ptr.as_ptr(), self.len) };
+        self.header = header;
+        self.ptr = ptr;
+        self.capacity = capacity;
+        Ok(())
+    }
+}
+
+impl<T, A> Extend<T> for SliceBuilder<'_, T, A>
+where
+    A: ArenaAlloc,
+{
+    /// Push all the items of the iterator.
+    ///
+    /// Panics if reserving space for the slice fails.
+    fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
+        for value in iter {
+            self.push(value);
+        }
+    }
+}
+
+impl<T, A> Drop for SliceBuilder<'_, T, A>
+where
+    A: ArenaAlloc,
+{
+    fn drop(&mut self) {
+        unsafe {
+            ptr::drop_in_place(ptr::slice_from_raw_parts_mut(self.ptr.as_ptr(), self.len));
+        }
+    }
+}
+
+impl<A> Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Create a [`StringBuilder`] coalescing strings into a single allocation
+    /// of this `Rodeo`.
+    #[must_use]
+    pub const fn string_builder(&self) -> StringBuilder<'_, A> {
+        StringBuilder {
+            rodeo: self,
+            buf: RawBuf::new(),
+            ranges: Vec::new(),
+        }
+    }
+
+    /// Create a [`Writer`] writing a string into this `Rodeo`.
+    #[must_use]
+    pub const fn writer(&self) -> Writer<'_, A> {
+        Writer {
+            rodeo: self,
+            buf: RawBuf::new(),
+        }
+    }
+
+    /// Create a [`SliceBuilder`] building a slice in this `Rodeo` without
+    /// knowing its length upfront.
+    #[must_use]
+    pub const fn slice_builder<T: 'static>(&self) -> SliceBuilder<'_, T, A> {
+        SliceBuilder {
+            rodeo: self,
+            header: None,
+            ptr: NonNull::dangling(),
+            len: 0,
+            capacity: 0,
+            marker: PhantomData,
+        }
+    }
+}
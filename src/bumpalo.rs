@@ -1,7 +1,12 @@
 //! Re-export of `bumpalo` crate and support for Rodeo.
 
 use core::alloc::Layout;
-use core::ptr::NonNull;
+use core::mem::MaybeUninit;
+use core::ptr::{self, NonNull};
+use core::slice;
+use core::sync::atomic::{compiler_fence, Ordering};
+
+use allocator_api2::alloc::Allocator;
 
 #[doc(no_inline)]
 pub use ::bumpalo::*;
@@ -11,15 +16,162 @@ use super::ArenaAlloc;
 impl ArenaAlloc for Bump {
     type Error = AllocErr;
 
+    const CAN_RESET: bool = true;
+
+    const CAN_DEALLOC: bool = true;
+
     #[inline]
     fn try_alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, Self::Error> {
         self.try_alloc_layout(layout)
     }
+
+    fn zeroize(&mut self) {
+        // SAFETY: no allocation is performed during the iteration, and the
+        // exclusive borrow guarantees there is no reference to the chunks.
+        for (ptr, len) in unsafe { self.iter_allocated_chunks_raw() } {
+            for i in 0..len {
+                unsafe { ptr.add(i).write_volatile(0) };
+            }
+        }
+        compiler_fence(Ordering::SeqCst);
+    }
+
+    fn reset(&mut self) {
+        self.reset();
+    }
+
+    fn contains(&self, ptr: NonNull<u8>) -> bool {
+        let address = ptr.as_ptr() as usize;
+        // SAFETY: no allocation is performed during the iteration, and the
+        // chunks are not accessed.
+        unsafe { self.iter_allocated_chunks_raw() }
+            .any(|(start, len)| (start as usize..start as usize + len).contains(&address))
+    }
+
+    fn is_last_allocation(&self, ptr: NonNull<u8>, _layout: Layout) -> bool {
+        // a zero-sized allocation returns the tip of the current chunk without
+        // moving it, and _bumpalo_ allocates downward
+        self.alloc_layout(Layout::new::<()>()) == ptr
+    }
+
+    unsafe fn dealloc_last(&self, ptr: NonNull<u8>, layout: Layout) {
+        unsafe { Allocator::deallocate(&self, ptr, layout) };
+    }
 }
 
 /// Convenient alias for a bumpalo-back Rodeo.
 pub type Rodeo = crate::Rodeo<Bump>;
 
+impl Rodeo {
+    /// Create a new `Rodeo` whose first chunk can hold at least `bytes`
+    /// bytes, headers included, to avoid growing it early.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::bumpalo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::with_capacity(64 * 1024);
+    /// let reserved = rodeo.allocator().allocated_bytes();
+    /// for i in 0..1000 {
+    ///     let _ = rodeo.alloc(i);
+    /// }
+    /// assert_eq!(rodeo.allocator().allocated_bytes(), reserved);
+    /// ```
+    #[must_use]
+    pub fn with_capacity(bytes: usize) -> Self {
+        Self::with_allocator(Bump::with_capacity(bytes))
+    }
+
+    /// Return the fraction of the memory reserved by the underlying [`Bump`]
+    /// that was actually requested through this `Rodeo`, headers included.
+    ///
+    /// A low utilization denotes slack, because of over-reservation or
+    /// fragmentation. Memory allocated directly with the allocator is not
+    /// counted as requested. Returns 0 if no memory was reserved yet.
+    #[must_use]
+    pub fn utilization(&self) -> f64 {
+        let capacity = self.allocator().allocated_bytes();
+        if capacity == 0 {
+            return 0.0;
+        }
+        #[allow(clippy::cast_precision_loss)]
+        let utilization = self.allocated.get() as f64 / capacity as f64;
+        utilization
+    }
+
+    /// Return the unused space of the current chunk as scratch space.
+    ///
+    /// Nothing is allocated: the scratch space is handed out by the next
+    /// allocations, and only the bytes committed with [`Rodeo::commit`] are
+    /// kept. This avoids a separate buffer for algorithms working in place
+    /// before knowing the size of their result. The scratch space is empty if
+    /// the current chunk is full.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::bumpalo::Rodeo;
+    ///
+    /// let mut rodeo = Rodeo::new();
+    /// let _ = rodeo.alloc(0_u64);
+    /// let scratch = rodeo.scratch();
+    /// let mut len = 0;
+    /// for b in "a1b2c3".bytes().filter(u8::is_ascii_digit) {
+    ///     scratch[len].write(b);
+    ///     len += 1;
+    /// }
+    /// let digits = unsafe { rodeo.commit(len) };
+    /// assert_eq!(digits, b"123");
+    /// ```
+    pub fn scratch(&mut self) -> &mut [MaybeUninit<u8>] {
+        let (start, capacity) = self.scratch_space();
+        unsafe { slice::from_raw_parts_mut(start.cast(), capacity) }
+    }
+
+    /// Allocate the first `n` bytes of the scratch space (see
+    /// [`Rodeo::scratch`]) and return an exclusive reference to them.
+    ///
+    /// The committed bytes are moved to their final place, since _bumpalo_
+    /// allocates downward.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `n` is larger than the scratch space.
+    ///
+    /// # Safety
+    ///
+    /// The first `n` bytes of the scratch space must have been initialized,
+    /// and nothing must have been allocated in this `Rodeo` since the call to
+    /// [`Rodeo::scratch`].
+    pub unsafe fn commit(&self, n: usize) -> &mut [u8] {
+        let (start, capacity) = self.scratch_space();
+        assert!(n <= capacity, "committed more than the scratch space");
+
+        // SAFETY: byte arrays cannot overflow when smaller than the chunk
+        let layout = unsafe { Layout::from_size_align_unchecked(n, 1) };
+        // fits in the current chunk, at the top of the scratch space
+        let ptr = self
+            .alloc_layout(layout)
+            .unwrap_or_else(|_| self.out_of_memory());
+        debug_assert_eq!(ptr.as_ptr(), start.wrapping_add(capacity - n));
+        unsafe {
+            ptr::copy(start, ptr.as_ptr(), n);
+            slice::from_raw_parts_mut(ptr.as_ptr(), n)
+        }
+    }
+
+    /// Return the start and the length of the unused space of the current
+    /// chunk.
+    fn scratch_space(&self) -> (*mut u8, usize) {
+        let capacity = self.allocator().chunk_capacity();
+        // a zero-sized allocation returns the tip of the current chunk
+        // without moving it
+        let tip = self.allocator().alloc_layout(Layout::new::<()>());
+        (unsafe { tip.as_ptr().sub(capacity) }, capacity)
+    }
+}
+
 #[test]
 fn test_bump() {
     let bump = Bump::new();
@@ -0,0 +1,186 @@
+//! Arena whose contents can be deep-cloned.
+
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::cell::RefCell;
+use core::ptr::NonNull;
+
+use crate::{ArenaAlloc, Rodeo, RodeoError};
+
+/// Function cloning a value of the source arena into another arena.
+type CloneFn<A> =
+    unsafe fn(NonNull<u8>, &Rodeo<A>) -> Result<NonNull<u8>, RodeoError<<A as ArenaAlloc>::Error>>;
+
+/// Clone glue of a value of type `T`.
+unsafe fn clone_into<T, A>(
+    value: NonNull<u8>,
+    rodeo: &Rodeo<A>,
+) -> Result<NonNull<u8>, RodeoError<A::Error>>
+where
+    T: Clone + 'static,
+    A: ArenaAlloc,
+{
+    let value = unsafe { value.cast::<T>().as_ref() };
+    let cloned = rodeo.try_alloc(value.clone())?;
+    Ok(NonNull::from(cloned).cast())
+}
+
+struct Entry<A: ArenaAlloc> {
+    value: NonNull<u8>,
+    type_id: TypeId,
+    clone: CloneFn<A>,
+}
+
+/// Arena of cloneable values, which can be deep-cloned into a new independent
+/// arena.
+///
+/// A [`Rodeo`] is untyped and cannot be cloned in general. This layer only
+/// accepts [`Clone`] values and records, for each of them, how to clone it.
+/// The values are identified by their index, in allocation order, which is
+/// preserved by [`CloneableRodeo::deep_clone`].
+///
+/// # Example
+///
+/// ```rust
+/// use rodeo::cloneable::CloneableRodeo;
+///
+/// let rodeo = CloneableRodeo::<rodeo::fallback::ChunkedAlloc>::default();
+/// let _ = rodeo.alloc(String::from("snapshot"));
+///
+/// let mut copy = rodeo.deep_clone();
+/// copy.get_mut::<String>(0).unwrap().push_str(" copy");
+/// assert_eq!(rodeo.get::<String>(0).unwrap(), "snapshot");
+/// assert_eq!(copy.get::<String>(0).unwrap(), "snapshot copy");
+/// ```
+pub struct CloneableRodeo<A: ArenaAlloc> {
+    rodeo: Rodeo<A>,
+    entries: RefCell<Vec<Entry<A>>>,
+}
+
+impl<A> Default for CloneableRodeo<A>
+where
+    A: ArenaAlloc + Default,
+{
+    fn default() -> Self {
+        Self::with_allocator(A::default())
+    }
+}
+
+impl<A> CloneableRodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Create a new cloneable arena based on the given arena allocator.
+    #[must_use]
+    pub const fn with_allocator(allocator: A) -> Self {
+        Self {
+            rodeo: Rodeo::with_allocator(allocator),
+            entries: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Allocate a cloneable value and return a shared reference to it.
+    ///
+    /// The reference is shared since the value may be accessed by index too.
+    /// Use [`CloneableRodeo::get_mut`] to mutate it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails.
+    pub fn alloc<T: Clone + 'static>(&self, value: T) -> &T {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc(value) {
+            ref_mut
+        } else {
+            self.rodeo.out_of_memory();
+        }
+    }
+
+    /// Try to allocate a cloneable value and return a shared reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails.
+    pub fn try_alloc<T: Clone + 'static>(&self, value: T) -> Result<&T, RodeoError<A::Error>> {
+        let ptr = NonNull::from(self.rodeo.try_alloc(value)?);
+        self.entries.borrow_mut().push(Entry {
+            value: ptr.cast(),
+            type_id: TypeId::of::<T>(),
+            clone: clone_into::<T, A>,
+        });
+        Ok(unsafe { ptr.as_ref() })
+    }
+
+    /// Return the number of values allocated in this arena.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    /// Return `true` if no value was allocated yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    /// Return a shared reference to the value of the given index, if it is of
+    /// type `T`.
+    #[must_use]
+    pub fn get<T: 'static>(&self, index: usize) -> Option<&T> {
+        let entries = self.entries.borrow();
+        let entry = entries.get(index)?;
+        (entry.type_id == TypeId::of::<T>()).then(|| unsafe { entry.value.cast().as_ref() })
+    }
+
+    /// Return an exclusive reference to the value of the given index, if it is
+    /// of type `T`.
+    #[must_use]
+    pub fn get_mut<T: 'static>(&mut self, index: usize) -> Option<&mut T> {
+        let entry = self.entries.get_mut().get(index)?;
+        (entry.type_id == TypeId::of::<T>()).then(|| unsafe { entry.value.cast().as_mut() })
+    }
+
+    /// Clone all the values into a new independent arena, with a default
+    /// allocator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the clones fails.
+    #[must_use]
+    pub fn deep_clone(&self) -> Self
+    where
+        A: Default,
+    {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(clone) = self.try_deep_clone() {
+            clone
+        } else {
+            self.rodeo.out_of_memory();
+        }
+    }
+
+    /// Try to clone all the values into a new independent arena, with a
+    /// default allocator.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the clones fails.
+    pub fn try_deep_clone(&self) -> Result<Self, RodeoError<A::Error>>
+    where
+        A: Default,
+    {
+        let clone = Self::default();
+        let entries = self.entries.borrow();
+        let mut cloned_entries = Vec::with_capacity(entries.len());
+        for entry in entries.iter() {
+            let value = unsafe { (entry.clone)(entry.value, &clone.rodeo) }?;
+            cloned_entries.push(Entry {
+                value,
+                type_id: entry.type_id,
+                clone: entry.clone,
+            });
+        }
+        *clone.entries.borrow_mut() = cloned_entries;
+        Ok(clone)
+    }
+}
@@ -0,0 +1,153 @@
+//! Arena of plain data, without any finalizer.
+
+use core::alloc::Layout;
+use core::{ptr, slice, str};
+
+use crate::{oom, ArenaAlloc, RodeoError};
+
+/// Arena restricted to [`Copy`] values, which never need to be dropped.
+///
+/// Unlike a [`Rodeo`](crate::Rodeo), no finalizer can ever be registered:
+/// there is no header, no finalizer chain, and dropping the arena only drops
+/// the allocator. Types with drop glue are rejected at compile time.
+///
+/// # Example
+///
+/// ```rust
+/// use rodeo::data::DataRodeo;
+///
+/// let arena = DataRodeo::<rodeo::fallback::ChunkedAlloc>::default();
+/// let point = arena.alloc((1.0, 2.0));
+/// point.0 += 1.0;
+/// assert_eq!(arena.alloc_str("plain"), "plain");
+/// ```
+///
+/// Values that need to be dropped do not compile:
+///
+/// ```compile_fail
+/// use rodeo::data::DataRodeo;
+///
+/// let arena = DataRodeo::<rodeo::fallback::ChunkedAlloc>::default();
+/// let _ = arena.alloc(String::from("needs drop"));
+/// ```
+#[derive(Default)]
+pub struct DataRodeo<A: ArenaAlloc> {
+    allocator: A,
+}
+
+impl<A> DataRodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Create a new data arena based on the given arena allocator.
+    #[must_use]
+    pub const fn with_allocator(allocator: A) -> Self {
+        Self { allocator }
+    }
+
+    /// Return a shared reference to the underlying allocator.
+    pub const fn allocator(&self) -> &A {
+        &self.allocator
+    }
+
+    /// Convert into the underlying allocator.
+    ///
+    /// Nothing is leaked, since no value needs to be dropped.
+    #[allow(clippy::missing_const_for_fn)] // destructors cannot be evaluated at compile-time
+    pub fn into_allocator(self) -> A {
+        self.allocator
+    }
+
+    /// Allocate a value and return an exclusive reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` fails.
+    pub fn alloc<T: Copy>(&self, value: T) -> &mut T {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc(value) {
+            ref_mut
+        } else {
+            oom();
+        }
+    }
+
+    /// Try to allocate a value and return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails.
+    pub fn try_alloc<T: Copy>(&self, value: T) -> Result<&mut T, RodeoError<A::Error>> {
+        let ptr = self
+            .allocator
+            .try_alloc_layout(Layout::new::<T>())?
+            .cast::<T>()
+            .as_ptr();
+        unsafe {
+            ptr.write(value);
+            Ok(&mut *ptr)
+        }
+    }
+
+    /// Allocate a slice by copying the input slice and return an exclusive
+    /// reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_copy<T: Copy>(&self, value: &[T]) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_slice_copy(value) {
+            ref_mut
+        } else {
+            oom();
+        }
+    }
+
+    /// Try to allocate a slice by copying the input slice and return an
+    /// exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    pub fn try_alloc_slice_copy<T: Copy>(
+        &self,
+        value: &[T],
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        let ptr = self
+            .allocator
+            .try_alloc_layout(Layout::for_value(value))?
+            .cast::<T>()
+            .as_ptr();
+        unsafe {
+            ptr::copy_nonoverlapping(value.as_ptr(), ptr, value.len());
+            Ok(slice::from_raw_parts_mut(ptr, value.len()))
+        }
+    }
+
+    /// Allocate a string slice by copying the input string slice and return
+    /// an exclusive reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the string fails.
+    pub fn alloc_str(&self, value: &str) -> &mut str {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_str(value) {
+            ref_mut
+        } else {
+            oom();
+        }
+    }
+
+    /// Try to allocate a string slice by copying the input string slice and
+    /// return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the string fails.
+    pub fn try_alloc_str(&self, value: &str) -> Result<&mut str, RodeoError<A::Error>> {
+        let bytes = self.try_alloc_slice_copy(value.as_bytes())?;
+        Ok(unsafe { str::from_utf8_unchecked_mut(bytes) })
+    }
+}
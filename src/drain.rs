@@ -0,0 +1,122 @@
+//! Draining iterator over arena slices.
+
+use core::iter::FusedIterator;
+use core::marker::PhantomData;
+use core::{fmt, ptr, slice};
+
+use crate::{ArenaAlloc, Rodeo};
+
+/// Iterator moving the elements out of an arena slice.
+///
+/// Elements not consumed by the iteration are dropped with the iterator.
+///
+/// Created by [`Rodeo::drain_slice`].
+pub struct SliceDrain<'s, T> {
+    ptr: *mut T,
+    start: usize,
+    end: usize,
+    marker: PhantomData<&'s mut [T]>,
+}
+
+impl<T> SliceDrain<'_, T> {
+    /// Return the remaining elements as a slice.
+    #[must_use]
+    pub const fn as_slice(&self) -> &[T] {
+        unsafe { slice::from_raw_parts(self.ptr.add(self.start), self.end - self.start) }
+    }
+}
+
+impl<T> Iterator for SliceDrain<'_, T> {
+    type Item = T;
+
+    fn next(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        let item = unsafe { self.ptr.add(self.start).read() };
+        self.start += 1;
+        Some(item)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        let len = self.end - self.start;
+        (len, Some(len))
+    }
+}
+
+impl<T> DoubleEndedIterator for SliceDrain<'_, T> {
+    fn next_back(&mut self) -> Option<T> {
+        if self.start == self.end {
+            return None;
+        }
+        self.end -= 1;
+        Some(unsafe { self.ptr.add(self.end).read() })
+    }
+}
+
+impl<T> ExactSizeIterator for SliceDrain<'_, T> {}
+
+impl<T> FusedIterator for SliceDrain<'_, T> {}
+
+impl<T: fmt::Debug> fmt::Debug for SliceDrain<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("SliceDrain").field(&self.as_slice()).finish()
+    }
+}
+
+impl<T> Drop for SliceDrain<'_, T> {
+    fn drop(&mut self) {
+        let remaining =
+            ptr::slice_from_raw_parts_mut(self.ptr.wrapping_add(self.start), self.end - self.start);
+        // consume first, in case a drop panics
+        self.start = self.end;
+        unsafe { ptr::drop_in_place(remaining) };
+    }
+}
+
+impl<A> Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Return an iterator moving the elements out of a slice allocated in
+    /// this `Rodeo`, like [`Vec::into_iter`](alloc::vec::Vec::into_iter).
+    ///
+    /// The slice finalizer is neutralized: the elements are dropped either by
+    /// the caller once yielded, or by the iterator itself if not consumed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` needs to be dropped and the slice was not allocated in
+    /// this `Rodeo` with a finalizer (e.g. by [`Rodeo::alloc_slice_clone`]), or
+    /// if the slice was already drained.
+    ///
+    /// # Safety
+    ///
+    /// The slice must be an entire slice allocated in this `Rodeo`, and must
+    /// not be used anymore after the call, as its elements are moved out.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let words = rodeo.alloc_slice_clone(&["a".to_string(), "b".to_string()]);
+    /// let mut drain = unsafe { rodeo.drain_slice(words) };
+    /// let a: String = drain.next().unwrap();
+    /// assert_eq!(a, "a");
+    /// ```
+    pub unsafe fn drain_slice<'s, T>(&self, slice: &'s mut [T]) -> SliceDrain<'s, T> {
+        let len = slice.len();
+        let ptr = slice.as_mut_ptr();
+        if len != 0 {
+            self.neutralize_slice_finalizer(ptr, len);
+        }
+        SliceDrain {
+            ptr,
+            start: 0,
+            end: len,
+            marker: PhantomData,
+        }
+    }
+}
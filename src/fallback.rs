@@ -1,8 +1,12 @@
-//! Fallback arena allocators for debugging purposes.
+//! Fallback arena allocators, for use without _bumpalo_ or for debugging
+//! purposes.
 
-use alloc::alloc::alloc;
+use alloc::alloc::{alloc, dealloc};
+use alloc::vec::Vec;
 use core::alloc::Layout;
-use core::ptr::NonNull;
+use core::cell::{Cell, RefCell};
+use core::ptr::{self, NonNull};
+use core::sync::atomic::{compiler_fence, Ordering};
 
 use crate::ArenaAlloc;
 
@@ -11,11 +15,16 @@ use crate::ArenaAlloc;
 pub struct LeakingAlloc;
 
 /// Allocation error.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub struct AllocErr;
 
 impl ArenaAlloc for LeakingAlloc {
     type Error = AllocErr;
     fn try_alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, Self::Error> {
+        if layout.size() == 0 {
+            // the global allocator does not support zero-sized allocations
+            return NonNull::new(layout.align() as *mut u8).ok_or(AllocErr);
+        }
         NonNull::new(unsafe { alloc(layout) }).ok_or(AllocErr)
     }
 }
@@ -29,3 +38,219 @@ impl ArenaAlloc for FailingAlloc {
         Err(AllocErr)
     }
 }
+
+/// Growth policy of a [`ChunkedAlloc`], computing the size of each new chunk
+/// from the size of the previous one.
+#[derive(Debug, Clone, Copy)]
+pub enum GrowthPolicy {
+    /// Double the size of each new chunk.
+    Double,
+    /// Use the same size for all the chunks after the first one.
+    Fixed(usize),
+    /// Compute the size of the next chunk with the given function.
+    Custom(fn(usize) -> usize),
+}
+
+impl GrowthPolicy {
+    fn next_size(self, previous: usize) -> usize {
+        match self {
+            Self::Double => previous.saturating_mul(2),
+            Self::Fixed(size) => size,
+            Self::Custom(f) => f(previous),
+        }
+    }
+}
+
+/// Allocation event reported to the observer of a [`ChunkedAlloc`] (see
+/// [`ChunkedAlloc::set_observer`]).
+///
+/// Only chunk allocations reach the global allocator: a memory profiler
+/// hooked there sees the arena overhead as a few large blocks, which the
+/// value allocations then fill. Tagging both kinds of events lets it
+/// attribute the memory of the chunks to the values carved out of them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AllocEvent {
+    /// A new chunk of the given layout was requested from the global
+    /// allocator.
+    ChunkAlloc(Layout),
+    /// A value of the given layout was carved out of a chunk.
+    ValueAlloc(Layout),
+}
+
+/// Alignment of every chunk of a [`ChunkedAlloc`].
+const CHUNK_ALIGN: usize = 16;
+
+/// Size of the first chunk of a default [`ChunkedAlloc`].
+const DEFAULT_FIRST_CHUNK_SIZE: usize = 4096;
+
+/// Arena allocator carving allocations out of chunks of memory obtained from
+/// the global allocator, and freeing them when dropped.
+///
+/// When the current chunk is exhausted, a new chunk is allocated whose size
+/// is decided by the [`GrowthPolicy`] (or larger, if the allocation would not
+/// fit otherwise).
+pub struct ChunkedAlloc {
+    chunks: RefCell<Vec<(NonNull<u8>, Layout)>>,
+    cursor: Cell<*mut u8>,
+    end: Cell<*mut u8>,
+    next_size: Cell<usize>,
+    policy: GrowthPolicy,
+    observer: Option<fn(AllocEvent)>,
+}
+
+impl ChunkedAlloc {
+    /// Create a new chunked allocator, doubling the size of its chunks.
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_policy(DEFAULT_FIRST_CHUNK_SIZE, GrowthPolicy::Double)
+    }
+
+    /// Create a new chunked allocator, with the given size for the first
+    /// chunk and growth policy for the next ones.
+    ///
+    /// No memory is allocated until the first allocation.
+    #[must_use]
+    pub const fn with_policy(first_chunk_size: usize, policy: GrowthPolicy) -> Self {
+        Self {
+            chunks: RefCell::new(Vec::new()),
+            cursor: Cell::new(ptr::null_mut()),
+            end: Cell::new(ptr::null_mut()),
+            next_size: Cell::new(first_chunk_size),
+            policy,
+            observer: None,
+        }
+    }
+
+    /// Set the observer called on every allocation, tagged as a chunk or a
+    /// value allocation (see [`AllocEvent`]).
+    ///
+    /// An allocation that needs a new chunk reports the chunk first, then
+    /// the value. The observer is a plain function pointer so that it is
+    /// available without `std`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::fallback::{AllocEvent, ChunkedAlloc};
+    /// use rodeo::Rodeo;
+    ///
+    /// fn trace(event: AllocEvent) {
+    ///     if let AllocEvent::ChunkAlloc(layout) = event {
+    ///         println!("arena chunk of {} bytes", layout.size());
+    ///     }
+    /// }
+    ///
+    /// let mut alloc = ChunkedAlloc::new();
+    /// alloc.set_observer(trace);
+    /// let rodeo = Rodeo::with_allocator(alloc);
+    /// let _ = rodeo.alloc(42);
+    /// ```
+    pub fn set_observer(&mut self, observer: fn(AllocEvent)) {
+        self.observer = Some(observer);
+    }
+
+    fn notify(&self, event: AllocEvent) {
+        if let Some(observer) = self.observer {
+            observer(event);
+        }
+    }
+
+    /// Return the sizes of the chunks allocated so far, in allocation order.
+    #[must_use]
+    pub fn chunk_sizes(&self) -> Vec<usize> {
+        self.chunks
+            .borrow()
+            .iter()
+            .map(|(_, layout)| layout.size())
+            .collect()
+    }
+
+    fn carve(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        if layout.size() == 0 {
+            return NonNull::new(layout.align() as *mut u8).ok_or(AllocErr);
+        }
+
+        let cursor = self.cursor.get();
+        let available = self.end.get() as usize - cursor as usize;
+        let padding = cursor.align_offset(layout.align());
+        if padding <= available && layout.size() <= available - padding {
+            let ptr = cursor.wrapping_add(padding);
+            self.cursor.set(ptr.wrapping_add(layout.size()));
+            return NonNull::new(ptr).ok_or(AllocErr);
+        }
+
+        self.alloc_chunk(layout)
+    }
+
+    #[cold]
+    fn alloc_chunk(&self, layout: Layout) -> Result<NonNull<u8>, AllocErr> {
+        let planned_size = self.next_size.get();
+        let size = planned_size.max(layout.size());
+        let chunk_layout =
+            Layout::from_size_align(size, layout.align().max(CHUNK_ALIGN)).map_err(|_| AllocErr)?;
+
+        let chunk = NonNull::new(unsafe { alloc(chunk_layout) }).ok_or(AllocErr)?;
+        self.chunks.borrow_mut().push((chunk, chunk_layout));
+        self.notify(AllocEvent::ChunkAlloc(chunk_layout));
+        self.next_size.set(self.policy.next_size(planned_size));
+
+        let start = chunk.as_ptr();
+        self.cursor.set(start.wrapping_add(layout.size()));
+        self.end.set(start.wrapping_add(size));
+        Ok(chunk)
+    }
+}
+
+impl Default for ChunkedAlloc {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ArenaAlloc for ChunkedAlloc {
+    type Error = AllocErr;
+
+    const CAN_DEALLOC: bool = true;
+
+    fn try_alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, Self::Error> {
+        let ptr = self.carve(layout)?;
+        self.notify(AllocEvent::ValueAlloc(layout));
+        Ok(ptr)
+    }
+
+    fn zeroize(&mut self) {
+        for &(chunk, layout) in self.chunks.get_mut().iter() {
+            for i in 0..layout.size() {
+                unsafe { chunk.as_ptr().add(i).write_volatile(0) };
+            }
+        }
+        compiler_fence(Ordering::SeqCst);
+    }
+
+    fn contains(&self, ptr: NonNull<u8>) -> bool {
+        let address = ptr.as_ptr() as usize;
+        self.chunks.borrow().iter().any(|(chunk, layout)| {
+            let start = chunk.as_ptr() as usize;
+            (start..start + layout.size()).contains(&address)
+        })
+    }
+
+    fn is_last_allocation(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        // zero-sized allocations do not use the chunks
+        layout.size() == 0 || ptr.as_ptr().wrapping_add(layout.size()) == self.cursor.get()
+    }
+
+    unsafe fn dealloc_last(&self, ptr: NonNull<u8>, layout: Layout) {
+        if layout.size() != 0 {
+            self.cursor.set(ptr.as_ptr());
+        }
+    }
+}
+
+impl Drop for ChunkedAlloc {
+    fn drop(&mut self) {
+        for &(chunk, layout) in self.chunks.get_mut().iter() {
+            unsafe { dealloc(chunk.as_ptr(), layout) };
+        }
+    }
+}
@@ -0,0 +1,142 @@
+//! C strings support for Rodeo.
+
+use core::alloc::Layout;
+use core::ffi::{c_char, CStr};
+use core::{fmt, ptr, slice};
+
+use crate::{ArenaAlloc, Rodeo, RodeoError};
+
+/// Error of a C string allocation, when the input contains a NUL byte.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct NulError {
+    position: usize,
+}
+
+impl NulError {
+    /// Return the position of the NUL byte in the offending string.
+    #[must_use]
+    pub const fn nul_position(&self) -> usize {
+        self.position
+    }
+}
+
+impl fmt::Display for NulError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "nul byte found at position {}", self.position)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for NulError {}
+
+/// Check that the bytes do not contain any NUL byte.
+fn check_no_nul(bytes: &[u8]) -> Result<(), NulError> {
+    bytes
+        .iter()
+        .position(|&b| b == 0)
+        .map_or(Ok(()), |position| Err(NulError { position }))
+}
+
+impl<A> Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Allocate an array of C strings, i.e., a `char**`-style structure, and
+    /// return an exclusive reference to the array of pointers.
+    ///
+    /// Each string is copied into the arena with a NUL terminator, then the
+    /// pointers to these C strings are allocated as an array.
+    ///
+    /// # Errors
+    ///
+    /// Fails without allocating if any string contains a NUL byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the strings or the array fails.
+    pub fn alloc_cstr_array(&self, strings: &[&str]) -> Result<&mut [*const c_char], NulError> {
+        for string in strings {
+            check_no_nul(string.as_bytes())?;
+        }
+
+        let array = self.try_alloc_slice_with(strings.len(), |i| {
+            self.try_alloc_nul_terminated(strings[i].as_bytes())
+                .unwrap_or_else(|_| self.out_of_memory())
+                .as_ptr()
+        });
+        Ok(array.unwrap_or_else(|_| self.out_of_memory()))
+    }
+
+    /// Allocate a copy of a C string and return a shared reference to it.
+    ///
+    /// No finalizer is needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the string fails.
+    pub fn alloc_cstr(&self, s: &CStr) -> &CStr {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(cstr) = self.try_alloc_cstr(s) {
+            cstr
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Try to allocate a copy of a C string and return a shared reference to
+    /// it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the string fails.
+    pub fn try_alloc_cstr(&self, s: &CStr) -> Result<&CStr, RodeoError<A::Error>> {
+        self.try_alloc_nul_terminated(s.to_bytes())
+    }
+
+    /// Allocate a C string by copying bytes and appending a NUL terminator,
+    /// and return a shared reference to it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let cstr = rodeo.alloc_cstr_from_bytes(b"hello").unwrap();
+    /// assert_eq!(cstr.to_bytes_with_nul(), b"hello\0");
+    /// assert!(rodeo.alloc_cstr_from_bytes(b"he\0llo").is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails without allocating if the bytes contain a NUL byte.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the string fails.
+    pub fn alloc_cstr_from_bytes(&self, bytes: &[u8]) -> Result<&CStr, NulError> {
+        check_no_nul(bytes)?;
+        Ok(self
+            .try_alloc_nul_terminated(bytes)
+            .unwrap_or_else(|_| self.out_of_memory()))
+    }
+
+    /// Try to allocate a C string by copying bytes and appending a NUL
+    /// terminator.
+    ///
+    /// The bytes must not contain any NUL byte.
+    fn try_alloc_nul_terminated(&self, bytes: &[u8]) -> Result<&CStr, RodeoError<A::Error>> {
+        debug_assert!(check_no_nul(bytes).is_ok());
+
+        let len = bytes.len();
+        let layout = Layout::array::<u8>(len + 1).map_err(|_| RodeoError::LayoutOverflow)?;
+        let ptr = self.alloc_layout(layout)?.as_ptr();
+
+        unsafe {
+            ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, len);
+            ptr.add(len).write(0);
+            let bytes = slice::from_raw_parts(ptr, len + 1);
+            Ok(CStr::from_bytes_with_nul_unchecked(bytes))
+        }
+    }
+}
@@ -0,0 +1,128 @@
+//! Formatting support for Rodeo, without `std`.
+
+use core::fmt::{self, Write};
+use core::{mem, str};
+
+use crate::{ArenaAlloc, Rodeo, RodeoError};
+
+/// Format the arguments into a string slice allocated in a [`Rodeo`] and
+/// return an exclusive reference to it.
+///
+/// This is a shorthand for [`Rodeo::alloc_fmt`] with [`format_args!`].
+///
+/// # Example
+///
+/// ```rust
+/// use rodeo::Rodeo;
+///
+/// let rodeo = Rodeo::new();
+/// let s = rodeo::format!(rodeo, "{}-{:03}", "id", 7);
+/// assert_eq!(s, "id-007");
+/// ```
+///
+/// # Panics
+///
+/// Panics if reserving space for the string fails, or if a formatting trait
+/// implementation returns an error or formats inconsistently.
+#[macro_export]
+macro_rules! format {
+    ($rodeo:expr, $($arg:tt)*) => {
+        $rodeo.alloc_fmt(::core::format_args!($($arg)*))
+    };
+}
+
+/// Writer only measuring the length of the output.
+struct Measure(usize);
+
+impl Write for Measure {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        self.0 = self.0.checked_add(s.len()).ok_or(fmt::Error)?;
+        Ok(())
+    }
+}
+
+/// Writer filling a preallocated buffer.
+struct Fill<'a>(&'a mut [u8]);
+
+impl Write for Fill<'_> {
+    fn write_str(&mut self, s: &str) -> fmt::Result {
+        if s.len() > self.0.len() {
+            return Err(fmt::Error);
+        }
+        let (head, tail) = mem::take(&mut self.0).split_at_mut(s.len());
+        head.copy_from_slice(s.as_bytes());
+        self.0 = tail;
+        Ok(())
+    }
+}
+
+impl<A> Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Format the arguments into a string slice allocated in this `Rodeo` and
+    /// return an exclusive reference to it.
+    ///
+    /// The arguments are formatted twice, once to measure the output and once
+    /// to write it, so that no intermediate buffer is needed. As such, it is
+    /// available without `std`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let s = rodeo.alloc_fmt(format_args!("{}-{:03}", "id", 7));
+    /// assert_eq!(s, "id-007");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the string fails, or if a formatting
+    /// trait implementation returns an error or formats inconsistently.
+    pub fn alloc_fmt(&self, args: fmt::Arguments<'_>) -> &mut str {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_fmt(args) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Try to format the arguments into a string slice allocated in this
+    /// `Rodeo` and return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the string fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a formatting trait implementation returns an error or formats
+    /// inconsistently.
+    pub fn try_alloc_fmt(
+        &self,
+        args: fmt::Arguments<'_>,
+    ) -> Result<&mut str, RodeoError<A::Error>> {
+        if let Some(s) = args.as_str() {
+            return self.try_alloc_str(s);
+        }
+
+        let mut measure = Measure(0);
+        measure
+            .write_fmt(args)
+            .expect("a formatting trait implementation returned an error");
+
+        let bytes = self.try_alloc_slice_with(measure.0, |_| 0u8)?;
+        let mut fill = Fill(&mut *bytes);
+        fill.write_fmt(args)
+            .expect("a formatting trait implementation formatted inconsistently");
+        assert!(
+            fill.0.is_empty(),
+            "a formatting trait implementation formatted inconsistently"
+        );
+
+        Ok(unsafe { str::from_utf8_unchecked_mut(bytes) })
+    }
+}
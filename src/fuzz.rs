@@ -0,0 +1,56 @@
+//! Replayable allocation logs, to reproduce fuzzing failures deterministically.
+
+use alloc::vec::Vec;
+use core::alloc::Layout;
+
+use crate::{noop_finalizer, ArenaAlloc, Rodeo, RodeoError};
+
+/// A single allocation operation, as recorded by a [`Rodeo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocOp {
+    /// Memory layout of the allocated data (excluding any header).
+    pub layout: Layout,
+
+    /// Memory layout of the finalizer data (e.g. the length of a slice), if
+    /// a finalizer was registered for the allocation.
+    pub finalizer_data: Option<Layout>,
+}
+
+impl<A> Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Return the log of all the allocations done so far, in order.
+    pub fn replay_log(&self) -> Vec<AllocOp> {
+        self.log.borrow().clone()
+    }
+}
+
+impl<A> Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Replay a log of allocations in this `Rodeo`.
+    ///
+    /// The allocations are left uninitialized and their finalizers do nothing,
+    /// but the sequence of layouts requested from the allocator is the same as
+    /// the one that was recorded, headers and finalizer data included. The
+    /// finalizer data is zeroed.
+    ///
+    /// # Errors
+    ///
+    /// Fails on the first allocation failure.
+    pub fn replay(&self, log: &[AllocOp]) -> Result<(), RodeoError<A::Error>> {
+        for op in log {
+            if let Some(finalizer_data) = op.finalizer_data {
+                let (header, finalizer_data_ptr, _) =
+                    self.reserve_header(None, op.layout, noop_finalizer, finalizer_data)?;
+                unsafe { finalizer_data_ptr.write_bytes(0, finalizer_data.size()) };
+                self.link(header);
+            } else {
+                self.alloc_layout(op.layout)?;
+            }
+        }
+        Ok(())
+    }
+}
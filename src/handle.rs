@@ -0,0 +1,192 @@
+//! Arena addressing its values by copyable handles.
+
+use alloc::vec::Vec;
+use core::any::TypeId;
+use core::cell::RefCell;
+use core::fmt;
+use core::hash::{Hash, Hasher};
+use core::marker::PhantomData;
+use core::ptr::NonNull;
+
+use crate::{ArenaAlloc, Rodeo, RodeoError};
+
+/// Lightweight handle to a value of type `T` in a [`HandleRodeo`].
+///
+/// A handle is a mere index, which can be copied freely and stored in other
+/// values of the arena without borrowing it.
+pub struct Handle<T> {
+    index: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<T> Handle<T> {
+    /// Return the index of the value, in allocation order.
+    #[must_use]
+    pub const fn index(self) -> usize {
+        self.index
+    }
+}
+
+impl<T> Clone for Handle<T> {
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T> Copy for Handle<T> {}
+
+impl<T> PartialEq for Handle<T> {
+    fn eq(&self, other: &Self) -> bool {
+        self.index == other.index
+    }
+}
+
+impl<T> Eq for Handle<T> {}
+
+impl<T> Hash for Handle<T> {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        self.index.hash(state);
+    }
+}
+
+impl<T> fmt::Debug for Handle<T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_tuple("Handle").field(&self.index).finish()
+    }
+}
+
+struct Entry {
+    value: NonNull<u8>,
+    type_id: TypeId,
+}
+
+/// Arena whose values are addressed by [`Handle`]s rather than references.
+///
+/// Each value is recorded in a side table of pointers, at the index of its
+/// handle. This decouples the identity of the values from the borrow of the
+/// arena, for instance to build graphs whose nodes refer to each other by
+/// handle.
+///
+/// # Example
+///
+/// ```rust
+/// use rodeo::handle::{Handle, HandleRodeo};
+///
+/// struct Node {
+///     name: String,
+///     edges: Vec<Handle<Node>>,
+/// }
+///
+/// let mut rodeo = HandleRodeo::<rodeo::fallback::ChunkedAlloc>::default();
+/// let a = rodeo.alloc_handle(Node { name: "a".into(), edges: vec![] });
+/// let b = rodeo.alloc_handle(Node { name: "b".into(), edges: vec![a] });
+/// rodeo.get_mut(a).edges.push(b);
+///
+/// let next = rodeo.get(a).edges[0];
+/// assert_eq!(rodeo.get(next).name, "b");
+/// ```
+pub struct HandleRodeo<A: ArenaAlloc> {
+    rodeo: Rodeo<A>,
+    entries: RefCell<Vec<Entry>>,
+}
+
+impl<A> Default for HandleRodeo<A>
+where
+    A: ArenaAlloc + Default,
+{
+    fn default() -> Self {
+        Self::with_allocator(A::default())
+    }
+}
+
+impl<A> HandleRodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Create a new handle arena based on the given arena allocator.
+    #[must_use]
+    pub const fn with_allocator(allocator: A) -> Self {
+        Self {
+            rodeo: Rodeo::with_allocator(allocator),
+            entries: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Allocate a value and return a handle to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails.
+    pub fn alloc_handle<T: 'static>(&self, value: T) -> Handle<T> {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(handle) = self.try_alloc_handle(value) {
+            handle
+        } else {
+            self.rodeo.out_of_memory();
+        }
+    }
+
+    /// Try to allocate a value and return a handle to it.
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails.
+    pub fn try_alloc_handle<T: 'static>(
+        &self,
+        value: T,
+    ) -> Result<Handle<T>, RodeoError<A::Error>> {
+        let ptr = NonNull::from(self.rodeo.try_alloc(value)?);
+        let mut entries = self.entries.borrow_mut();
+        entries.push(Entry {
+            value: ptr.cast(),
+            type_id: TypeId::of::<T>(),
+        });
+        Ok(Handle {
+            index: entries.len() - 1,
+            marker: PhantomData,
+        })
+    }
+
+    /// Return the number of values allocated in this arena.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.entries.borrow().len()
+    }
+
+    /// Return `true` if no value was allocated yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.entries.borrow().is_empty()
+    }
+
+    /// Return a shared reference to the value of the given handle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle comes from another arena and does not resolve to
+    /// a value of type `T` in this one.
+    #[must_use]
+    pub fn get<T: 'static>(&self, handle: Handle<T>) -> &T {
+        let value = Self::resolve(&self.entries.borrow(), handle);
+        unsafe { value.cast().as_ref() }
+    }
+
+    /// Return an exclusive reference to the value of the given handle.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the handle comes from another arena and does not resolve to
+    /// a value of type `T` in this one.
+    #[must_use]
+    pub fn get_mut<T: 'static>(&mut self, handle: Handle<T>) -> &mut T {
+        let value = Self::resolve(self.entries.get_mut(), handle);
+        unsafe { value.cast().as_mut() }
+    }
+
+    fn resolve<T: 'static>(entries: &[Entry], handle: Handle<T>) -> NonNull<u8> {
+        match entries.get(handle.index) {
+            Some(entry) if entry.type_id == TypeId::of::<T>() => entry.value,
+            _ => panic!("invalid handle"),
+        }
+    }
+}
@@ -0,0 +1,59 @@
+//! Re-export of `hashbrown` crate and support for Rodeo.
+
+use core::alloc::Layout;
+use core::ptr::NonNull;
+
+use allocator_api2::alloc::{AllocError, Allocator};
+
+#[doc(no_inline)]
+pub use ::hashbrown::*;
+
+use crate::{ArenaAlloc, Rodeo};
+
+/// A shared reference to a `Rodeo` can back `hashbrown` collections, e.g.
+/// `HashMap<K, V, DefaultHashBuilder, &Rodeo>`.
+///
+/// As with the unstable `allocator_api`, the collections own their entries
+/// and drop them themselves. Deallocation does nothing, the memory is only
+/// reclaimed with the arena.
+unsafe impl<A> Allocator for &Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    fn allocate(&self, layout: Layout) -> Result<NonNull<[u8]>, AllocError> {
+        let ptr = self.try_alloc_layout(layout).map_err(|_| AllocError)?;
+        Ok(NonNull::slice_from_raw_parts(ptr, layout.size()))
+    }
+
+    unsafe fn deallocate(&self, _ptr: NonNull<u8>, _layout: Layout) {}
+}
+
+impl<A> Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Create an empty hash map whose table is allocated in this `Rodeo`.
+    ///
+    /// The map owns its entries: the keys and values are dropped when the map
+    /// is dropped (or when they are removed), as with any `HashMap`, never by
+    /// the arena. The table memory is only reclaimed with the arena. As such,
+    /// growing the map abandons the previous table to the arena, so reserve
+    /// the needed capacity upfront when known.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let mut map = rodeo.alloc_hashmap();
+    /// map.reserve(2);
+    /// map.insert("one", String::from("1"));
+    /// map.insert("two", String::from("2"));
+    /// assert_eq!(map["two"], "2");
+    /// ```
+    #[must_use]
+    pub fn alloc_hashmap<K, V>(&self) -> HashMap<K, V, DefaultHashBuilder, &Self> {
+        HashMap::with_hasher_in(DefaultHashBuilder::default(), self)
+    }
+}
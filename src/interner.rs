@@ -0,0 +1,86 @@
+//! Slice interning on top of Rodeo.
+
+use core::cell::RefCell;
+use core::hash::Hash;
+use std::collections::HashSet;
+
+use crate::{ArenaAlloc, Rodeo, RodeoError};
+
+/// Interner of `Copy` slices, backed by a [`Rodeo`].
+///
+/// Equal slices are allocated only once and interning them again returns the
+/// very same arena slice. The interner borrows the arena, so the interned
+/// slices cannot outlive it.
+///
+/// # Example
+///
+/// ```rust
+/// use rodeo::interner::SliceInterner;
+/// use rodeo::Rodeo;
+///
+/// let rodeo = Rodeo::new();
+/// let interner = SliceInterner::new(&rodeo);
+/// let a = interner.intern_slice(&[1, 2, 3]);
+/// let b = interner.intern_slice(&vec![1, 2, 3]);
+/// assert!(core::ptr::eq(a, b));
+/// ```
+pub struct SliceInterner<'r, T, A: ArenaAlloc> {
+    rodeo: &'r Rodeo<A>,
+    set: RefCell<HashSet<&'r [T]>>,
+}
+
+impl<'r, T, A> SliceInterner<'r, T, A>
+where
+    T: Copy + Hash + Eq + 'static,
+    A: ArenaAlloc,
+{
+    /// Create a new empty interner allocating in the given `Rodeo`.
+    #[must_use]
+    pub fn new(rodeo: &'r Rodeo<A>) -> Self {
+        Self {
+            rodeo,
+            set: RefCell::new(HashSet::new()),
+        }
+    }
+
+    /// Intern a slice and return a shared reference to the arena copy.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn intern_slice(&self, slice: &[T]) -> &'r [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(interned) = self.try_intern_slice(slice) {
+            interned
+        } else {
+            self.rodeo.out_of_memory();
+        }
+    }
+
+    /// Try to intern a slice and return a shared reference to the arena copy.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    pub fn try_intern_slice(&self, slice: &[T]) -> Result<&'r [T], RodeoError<A::Error>> {
+        let mut set = self.set.borrow_mut();
+        if let Some(&interned) = set.get(slice) {
+            return Ok(interned);
+        }
+        let interned: &'r [T] = self.rodeo.try_alloc_slice_copy(slice)?;
+        set.insert(interned);
+        Ok(interned)
+    }
+
+    /// Return the number of distinct interned slices.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.set.borrow().len()
+    }
+
+    /// Return `true` if no slice has been interned yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.set.borrow().is_empty()
+    }
+}
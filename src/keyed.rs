@@ -0,0 +1,149 @@
+//! Arena dropping its values in key order.
+
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::cell::RefCell;
+use core::mem;
+use core::ptr::NonNull;
+
+use crate::{drop_finalizer, ArenaAlloc, Header, Rodeo, RodeoError};
+
+/// Arena whose values are dropped in ascending key order, independently of
+/// their allocation order.
+///
+/// Each value is allocated along with a key. When the arena is dropped, the
+/// keyed finalizers are sorted by key (ties keep their allocation order) and
+/// run in that order. This makes teardown deterministic, for instance for
+/// golden-output tests. Use [`Reverse`](core::cmp::Reverse) keys for a
+/// descending order.
+///
+/// The finalizers are registered in the underlying [`Rodeo`] as any other, so
+/// they count towards its finalizer limit and show in
+/// [`Rodeo::num_pending_finalizers`], [`Rodeo::pending_layouts`] and
+/// [`Rodeo::summary`]. The keys, however, are kept in a side table rather
+/// than in the finalizer data, since the chain of the `Rodeo` does not know
+/// their type.
+///
+/// # Example
+///
+/// ```rust
+/// use rodeo::keyed::KeyedRodeo;
+///
+/// let rodeo = KeyedRodeo::<u32, rodeo::fallback::ChunkedAlloc>::default();
+/// let _ = rodeo.alloc_with_key(2, String::from("dropped last"));
+/// let _ = rodeo.alloc_with_key(1, String::from("dropped first"));
+/// ```
+pub struct KeyedRodeo<K: Ord, A: ArenaAlloc> {
+    rodeo: Rodeo<A>,
+    finalizers: RefCell<Vec<(K, NonNull<Header>)>>,
+}
+
+impl<K, A> Default for KeyedRodeo<K, A>
+where
+    K: Ord,
+    A: ArenaAlloc + Default,
+{
+    fn default() -> Self {
+        Self::with_allocator(A::default())
+    }
+}
+
+impl<K, A> KeyedRodeo<K, A>
+where
+    K: Ord,
+    A: ArenaAlloc,
+{
+    /// Create a new keyed arena based on the given arena allocator.
+    #[must_use]
+    pub const fn with_allocator(allocator: A) -> Self {
+        Self {
+            rodeo: Rodeo::with_allocator(allocator),
+            finalizers: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Return the underlying `Rodeo`.
+    ///
+    /// Values allocated directly in it are dropped after all the keyed
+    /// values, in reverse allocation order as usual.
+    #[must_use]
+    pub const fn rodeo(&self) -> &Rodeo<A> {
+        &self.rodeo
+    }
+
+    /// Allocate a value along with its drop key and return an exclusive
+    /// reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails.
+    pub fn alloc_with_key<T: 'static>(&self, key: K, value: T) -> &mut T {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_with_key(key, value) {
+            ref_mut
+        } else {
+            self.rodeo.out_of_memory();
+        }
+    }
+
+    /// Try to allocate a value along with its drop key and return an
+    /// exclusive reference to it.
+    ///
+    /// The key is discarded if `T` does not need to be dropped.
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails.
+    pub fn try_alloc_with_key<T: 'static>(
+        &self,
+        key: K,
+        value: T,
+    ) -> Result<&mut T, RodeoError<A::Error>> {
+        if !mem::needs_drop::<T>() {
+            return self.rodeo.try_alloc(value);
+        }
+
+        let (header, ptr) =
+            self.rodeo
+                .reserve_with_finalizer(None, Layout::new::<T>(), drop_finalizer::<T>, ())?;
+        let ptr = ptr.cast::<T>();
+        unsafe { ptr.write(value) };
+        self.rodeo.link(header);
+        self.finalizers.borrow_mut().push((key, header));
+        Ok(unsafe { &mut *ptr })
+    }
+}
+
+impl<K, A> Drop for KeyedRodeo<K, A>
+where
+    K: Ord,
+    A: ArenaAlloc,
+{
+    fn drop(&mut self) {
+        let finalizers = self.finalizers.get_mut();
+        let mut keyed: Vec<_> = finalizers.iter().map(|&(_, header)| header).collect();
+        keyed.sort_unstable();
+
+        // take the keyed headers out of the chain in a single walk, skipping
+        // those that are no longer linked (e.g. forgotten)
+        let mut linked = Vec::with_capacity(keyed.len());
+        let mut next = None;
+        let mut current = self.rodeo.last.get();
+        while let Some(header) = current {
+            current = unsafe { header.as_ref() }.previous;
+            if keyed.binary_search(&header).is_ok() {
+                self.rodeo.unlink(next, header);
+                linked.push(header);
+            } else {
+                next = Some(header);
+            }
+        }
+        linked.sort_unstable();
+        finalizers.retain(|(_, header)| linked.binary_search(header).is_ok());
+
+        finalizers.sort_by(|(a, _), (b, _)| a.cmp(b));
+        for (_, header) in finalizers.drain(..) {
+            Header::finalize(header);
+        }
+    }
+}
@@ -1,26 +1,79 @@
 //! Fast dropping arena based on _bumpalo_.
 
 #![cfg_attr(not(feature = "std"), no_std)]
+#![cfg_attr(feature = "allocator_api", feature(allocator_api))]
 #![warn(unsafe_op_in_unsafe_fn)]
 #![warn(clippy::pedantic)]
 #![warn(clippy::nursery)]
 #![warn(clippy::cargo)]
+// handing out exclusive references from a shared arena is the whole point
+#![allow(clippy::mut_from_ref)]
 
 use core::alloc::Layout;
 use core::cell::Cell;
+use core::convert::Infallible;
+use core::mem::{ManuallyDrop, MaybeUninit};
+use core::pin::Pin;
 use core::ptr::NonNull;
-use core::{mem, ptr, slice};
+use core::str::Utf8Error;
+use core::{fmt, mem, ptr, slice};
 
 extern crate alloc;
 
+#[cfg(feature = "allocator_api")]
+mod allocator_api;
+
+#[cfg(feature = "arrayvec")]
+mod arrayvec;
+
 #[cfg(feature = "bumpalo")]
 pub mod bumpalo;
 
+pub mod builder;
+
+pub mod cloneable;
+
+pub mod data;
+
+pub mod drain;
+
 pub mod fallback;
 
+pub mod ffi;
+
+mod format;
+
+#[cfg(feature = "fuzz")]
+pub mod fuzz;
+
+pub mod handle;
+
+#[cfg(feature = "hashbrown")]
+pub mod hashbrown;
+
+pub mod keyed;
+
+pub mod node;
+
+pub mod slab;
+
+#[cfg(feature = "std")]
+pub mod sync;
+
+#[cfg(feature = "std")]
+pub mod interner;
+
 #[cfg(test)]
 mod tests;
 
+/// Arena-backed growable vector, whose remaining elements are dropped exactly
+/// once (see [`builder::SliceBuilder`]).
+pub type Vec<'r, T, A> = builder::SliceBuilder<'r, T, A>;
+
+/// Arena-backed growable string, yielding a `&str` with the lifetime of the
+/// arena (see [`builder::Writer`]).
+pub type String<'r, A> = builder::Writer<'r, A>;
+
 /// Arena allocator trait.
 ///
 /// Arena allocator do not have to provide a deallocation method.
@@ -29,18 +82,95 @@ pub trait ArenaAlloc {
     /// Error type used when the allocation fails.
     type Error;
 
+    /// Whether the allocator can reclaim all its memory at once for reuse,
+    /// without being dropped.
+    const CAN_RESET: bool = false;
+
+    /// Whether the allocator can deallocate individual allocations (at least
+    /// the last one).
+    const CAN_DEALLOC: bool = false;
+
+    /// Whether the allocator can reserve memory ahead of allocations.
+    const CAN_RESERVE: bool = false;
+
     /// Try to allocate memory for the given layout.
     ///
     /// # Errors
     ///
     /// If for whatever reasons the allocation fails, returns the given an error variant will be returned.
     fn try_alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, Self::Error>;
+
+    /// Try to allocate memory for the given layout, with a hint about the
+    /// region (e.g., the NUMA node) the memory should come from.
+    ///
+    /// The meaning of `region` is left to the allocator. By default, the hint
+    /// is ignored.
+    ///
+    /// # Errors
+    ///
+    /// If for whatever reasons the allocation fails, returns the given an error variant will be returned.
+    fn try_alloc_layout_in_region(
+        &self,
+        layout: Layout,
+        region: usize,
+    ) -> Result<NonNull<u8>, Self::Error> {
+        let _ = region;
+        self.try_alloc_layout(layout)
+    }
+
+    /// Overwrite all the memory handed out so far with zeros.
+    ///
+    /// The writes must not be optimized away, even if the memory is about to
+    /// be freed. With the `zeroize-on-reset` feature, a [`Rodeo`] calls this
+    /// method once all its data has been dropped.
+    ///
+    /// By default, nothing is done.
+    fn zeroize(&mut self) {}
+
+    /// Reclaim all the memory handed out so far, for reuse by the next
+    /// allocations.
+    ///
+    /// Only meaningful if [`ArenaAlloc::CAN_RESET`] is set. By default,
+    /// nothing is done.
+    fn reset(&mut self) {}
+
+    /// Check whether the given pointer points into memory handed out by this
+    /// allocator.
+    ///
+    /// This is only used for debugging purposes, to detect the corruption of
+    /// the finalizer chain. By default, it conservatively returns `true`.
+    fn contains(&self, ptr: NonNull<u8>) -> bool {
+        let _ = ptr;
+        true
+    }
+
+    /// Check whether the given allocation is the last one, and could be
+    /// reclaimed by [`ArenaAlloc::dealloc_last`].
+    ///
+    /// By default, returns `false`. Allocators setting
+    /// [`ArenaAlloc::CAN_DEALLOC`] should override it.
+    fn is_last_allocation(&self, ptr: NonNull<u8>, layout: Layout) -> bool {
+        let _ = (ptr, layout);
+        false
+    }
+
+    /// Reclaim the last allocation, for reuse by the next ones.
+    ///
+    /// By default, nothing is done.
+    ///
+    /// # Safety
+    ///
+    /// The allocation must be the last one (see
+    /// [`ArenaAlloc::is_last_allocation`]) and must not be used anymore.
+    unsafe fn dealloc_last(&self, ptr: NonNull<u8>, layout: Layout) {
+        let _ = (ptr, layout);
+    }
 }
 
 /// Header of a droppable allocation
 struct Header {
     /// Previous header
-    previous: Option<NonNull<Header>>,
+    previous: Option<NonNull<Self>>,
 
     /// Actual finalizer function
     finalizer: unsafe fn(NonNull<u8>),
@@ -49,8 +179,7 @@ struct Header {
     #[cfg(debug_assertions)]
     finalizer_data_layout: Layout,
 
-    /// Memory layout for debugging purposes only
-    #[cfg(debug_assertions)]
+    /// Memory layout of the data
     data_layout: Layout,
 }
 
@@ -63,6 +192,15 @@ impl Header {
     }
 }
 
+/// A finalizer that does nothing.
+#[cfg(any(test, feature = "fuzz"))]
+const unsafe fn noop_finalizer(_: NonNull<u8>) {}
+
+/// Compare two finalizers by address.
+fn same_finalizer(a: unsafe fn(NonNull<u8>), b: unsafe fn(NonNull<u8>)) -> bool {
+    a as *const () == b as *const ()
+}
+
 /// The generic "drop function".
 unsafe fn drop_finalizer<T>(non_null: NonNull<u8>) {
     let header_layout = Layout::new::<Header>();
@@ -102,14 +240,8 @@ unsafe fn slice_drop_finalizer<T>(non_null: NonNull<u8>) {
     unsafe {
         let header: &Header = &*ptr.cast();
 
-        let size = t_layout.size();
-        let align = t_layout.align();
-        // waiting for `alloc_layout_extra` stabilization
-        let padded_size = size.wrapping_add(align).wrapping_sub(1) & !align.wrapping_sub(1);
-        let slice_layout = Layout::from_size_align_unchecked(padded_size * len, t_layout.align());
-
         debug_assert_eq!(len_layout, header.finalizer_data_layout);
-        debug_assert_eq!(slice_layout, header.data_layout);
+        debug_assert_eq!(Layout::array::<T>(len).ok(), Some(header.data_layout));
     }
 
     let ptr: *mut T = ptr.wrapping_add(offset_t).cast();
@@ -119,11 +251,114 @@ unsafe fn slice_drop_finalizer<T>(non_null: NonNull<u8>) {
     }
 }
 
+/// Finalizer data of a custom finalizer (see
+/// [`Rodeo::try_alloc_with_finalizer`]).
+struct CustomFinalizer {
+    /// Finalizer given by the user, called on the data
+    finalizer: unsafe fn(NonNull<u8>),
+
+    /// Offset of the data from the header
+    data_offset: usize,
+}
+
+/// Header freshly reserved, with pointers to its finalizer data and to the
+/// actual data.
+type ReservedHeader = (NonNull<Header>, *mut u8, *mut u8);
+
+/// Call a custom finalizer on its data.
+unsafe fn custom_finalizer(non_null: NonNull<u8>) {
+    let (_, offset_fd) = HEADER_LAYOUT
+        .extend(Layout::new::<CustomFinalizer>())
+        .unwrap();
+
+    #[cfg(debug_assertions)]
+    {
+        let header = unsafe { non_null.cast::<Header>().as_ref() };
+        debug_assert_eq!(
+            Layout::new::<CustomFinalizer>(),
+            header.finalizer_data_layout
+        );
+    }
+
+    unsafe {
+        let bytes = non_null.as_ptr();
+        let custom: &CustomFinalizer = &*bytes.wrapping_add(offset_fd).cast();
+        let data = NonNull::new_unchecked(bytes.wrapping_add(custom.data_offset));
+        (custom.finalizer)(data);
+    }
+}
+
+/// Order in which a [`Rodeo`] drops its values.
+///
+/// A value whose [`Drop`] implementation reads another value of the same
+/// arena (through a raw pointer) requires the latter to be dropped after it.
+/// With the default reverse allocation order, this holds if the other value
+/// was allocated before. Dropping in allocation order supports the converse.
+/// No order supports arbitrary cross-references.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum DropOrder {
+    /// Drop in reverse allocation order, like local variables.
+    #[default]
+    Lifo,
+
+    /// Drop in allocation order.
+    ///
+    /// The finalizer chain is reversed in place before being run, in linear
+    /// time, so that the headers keep a single link.
+    Fifo,
+}
+
+/// Error of the fallible allocation methods of a [`Rodeo`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RodeoError<E> {
+    /// The underlying allocator failed.
+    Alloc(E),
+
+    /// The maximum number of pending finalizers has been reached
+    /// (see [`Rodeo::with_max_finalizers`]).
+    TooManyFinalizers,
+
+    /// The allocation would exceed the maximum number of bytes of the arena
+    /// (see [`Rodeo::with_limit`]).
+    LimitExceeded,
+
+    /// The requested size or alignment, once combined with the header if any,
+    /// cannot form a valid layout.
+    LayoutOverflow,
+}
+
+impl<E> From<E> for RodeoError<E> {
+    fn from(error: E) -> Self {
+        Self::Alloc(error)
+    }
+}
+
+impl<E: fmt::Display> fmt::Display for RodeoError<E> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Alloc(error) => write!(f, "allocation failed: {error}"),
+            Self::TooManyFinalizers => f.write_str("too many pending finalizers"),
+            Self::LimitExceeded => f.write_str("arena limit exceeded"),
+            Self::LayoutOverflow => f.write_str("layout overflow"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl<E: std::error::Error + 'static> std::error::Error for RodeoError<E> {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Alloc(error) => Some(error),
+            Self::TooManyFinalizers | Self::LimitExceeded | Self::LayoutOverflow => None,
+        }
+    }
+}
+
 #[cfg(feature = "bumpalo")]
 type Alloc = ::bumpalo::Bump;
 
 #[cfg(not(feature = "bumpalo"))]
-type Alloc = fallback::LeakingAlloc;
+type Alloc = fallback::ChunkedAlloc;
 
 /// An arena that cleanly drops allocated data.
 ///
@@ -143,31 +378,221 @@ type Alloc = fallback::LeakingAlloc;
 /// let n = rodeo.alloc(1);
 /// *n = 2;
 /// ```
-#[derive(Default)]
-pub struct Rodeo<A> {
+///
+/// # Thread safety
+///
+/// A `Rodeo` is neither [`Send`] nor [`Sync`], whatever the allocator.
+/// It may hold values that are not `Send` themselves (e.g. an [`Rc`]), which
+/// would be dropped on another thread if the arena was moved there. Also,
+/// allocating through a shared reference mutates the finalizer chain without
+/// synchronization.
+///
+/// [`Rc`]: alloc::rc::Rc
+///
+/// ```compile_fail
+/// fn assert_send<T: Send>(_: T) {}
+/// assert_send(rodeo::Rodeo::new());
+/// ```
+///
+/// ```compile_fail
+/// fn assert_sync<T: Sync>(_: &T) {}
+/// assert_sync(&rodeo::Rodeo::new());
+/// ```
+///
+/// The bookkeeping of the arena (finalizer chain and counters) lives in
+/// plain [`Cell`]s, without any atomic operation or memory ordering: it is
+/// only ever accessed from the thread owning the arena.
+///
+/// As a consequence, a future holding a `Rodeo` or a reference to it across
+/// an `.await` point is not `Send` either, and must be run on a local
+/// executor. References allocated from the arena can be held across `.await`
+/// points as long as the arena outlives the future.
+pub struct Rodeo<A: ArenaAlloc> {
     allocator: A,
     last: Cell<Option<NonNull<Header>>>,
+    finalizers: Cell<usize>,
+    max_finalizers: usize,
+    /// Maximum of `allocated`
+    max_bytes: usize,
+    /// Handler called by the infallible methods when allocating fails
+    oom_handler: fn() -> !,
+    drop_order: DropOrder,
+    next_id: Cell<u64>,
+    /// Number of resets so far
+    generation: Cell<u64>,
+    /// Bytes requested from the allocator, headers included
+    allocated: Cell<usize>,
+    /// Last allocation, if it may still be undone
+    last_alloc: Cell<Option<(NonNull<u8>, Layout)>>,
+    /// Thread the arena is bound to, set on first allocation
+    #[cfg(all(debug_assertions, feature = "std"))]
+    thread: Cell<Option<std::thread::ThreadId>>,
+    #[cfg(feature = "fuzz")]
+    log: core::cell::RefCell<alloc::vec::Vec<fuzz::AllocOp>>,
+}
+
+impl<A> Default for Rodeo<A>
+where
+    A: ArenaAlloc + Default,
+{
+    fn default() -> Self {
+        Self::with_allocator(A::default())
+    }
 }
 
 impl Rodeo<Alloc> {
     /// Create a new dropping allocator with a default allocator
-    /// (a [`bumpalo::Bump`] if the `bumpalo` feature is enabled).
+    /// (a [`bumpalo::Bump`] if the `bumpalo` feature is enabled, a
+    /// [`fallback::ChunkedAlloc`] otherwise).
     #[must_use]
     pub fn new() -> Self {
         Self::with_allocator(Alloc::default())
     }
 }
 
-impl<A> Rodeo<A> {
+impl<A> Rodeo<A>
+where
+    A: ArenaAlloc + Default,
+{
+    /// Create a new `Rodeo` with a default allocator, run `f` on it and return
+    /// both the arena and the result of `f`.
+    ///
+    /// This is a convenient entry point for builders: the result may not
+    /// borrow from the arena, but the arena is kept alive alongside it, for
+    /// instance to be reused or dropped later.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let (rodeo, len) = Rodeo::<rodeo::fallback::ChunkedAlloc>::build(|rodeo| {
+    ///     let words = rodeo.alloc_slice_clone(&["a".to_string(), "tree".to_string()]);
+    ///     words.iter().map(String::len).sum::<usize>()
+    /// });
+    /// assert_eq!(len, 5);
+    /// let _ = rodeo.alloc(len);
+    /// ```
+    pub fn build<R, F>(f: F) -> (Self, R)
+    where
+        F: FnOnce(&Self) -> R,
+    {
+        let rodeo = Self::default();
+        let result = f(&rodeo);
+        (rodeo, result)
+    }
+}
+
+impl<A> Rodeo<A>
+where
+    A: ArenaAlloc,
+{
     /// Create a new dropping allocator based on the given arena allocator.
     #[must_use]
     pub const fn with_allocator(allocator: A) -> Self {
+        Self::with_max_finalizers(allocator, usize::MAX)
+    }
+
+    /// Create a new dropping allocator based on the given arena allocator,
+    /// with at most `max_finalizers` pending finalizers.
+    ///
+    /// Once the maximum is reached, allocating droppable data fails with
+    /// [`RodeoError::TooManyFinalizers`] (or panics with the non-`try_`
+    /// methods), which guards against the runaway allocation of droppable
+    /// data. Allocating non-droppable data is unaffected.
+    #[must_use]
+    pub const fn with_max_finalizers(allocator: A, max_finalizers: usize) -> Self {
         Self {
             allocator,
             last: Cell::new(None),
+            finalizers: Cell::new(0),
+            max_finalizers,
+            max_bytes: usize::MAX,
+            oom_handler: oom,
+            drop_order: DropOrder::Lifo,
+            next_id: Cell::new(0),
+            generation: Cell::new(0),
+            allocated: Cell::new(0),
+            last_alloc: Cell::new(None),
+            #[cfg(all(debug_assertions, feature = "std"))]
+            thread: Cell::new(None),
+            #[cfg(feature = "fuzz")]
+            log: core::cell::RefCell::new(alloc::vec::Vec::new()),
         }
     }
 
+    /// Create a new dropping allocator based on the given arena allocator,
+    /// which may hand out at most `max_bytes` bytes.
+    ///
+    /// The budget is checked before calling the allocator, against the bytes
+    /// requested so far, headers included (see [`Rodeo::allocated_bytes`]).
+    /// Past it, allocating fails with [`RodeoError::LimitExceeded`] (or
+    /// panics with the non-`try_` methods). This is meant to sandbox
+    /// untrusted workloads.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::{Rodeo, RodeoError};
+    ///
+    /// let rodeo = Rodeo::with_limit(rodeo::fallback::ChunkedAlloc::new(), 16);
+    /// assert!(rodeo.try_alloc([0_u8; 16]).is_ok());
+    /// assert_eq!(rodeo.try_alloc(0_u8).unwrap_err(), RodeoError::LimitExceeded);
+    /// ```
+    #[must_use]
+    pub const fn with_limit(allocator: A, max_bytes: usize) -> Self {
+        let mut rodeo = Self::with_allocator(allocator);
+        rodeo.max_bytes = max_bytes;
+        rodeo
+    }
+
+    /// Create a new dropping allocator based on the given arena allocator,
+    /// dropping its values in the given order.
+    ///
+    /// The order applies whenever all the values are dropped: with the arena,
+    /// with [`Rodeo::drop_all`] or with [`Rodeo::reset`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::fallback::ChunkedAlloc;
+    /// use rodeo::{DropOrder, Rodeo};
+    ///
+    /// let rodeo = Rodeo::with_allocator_ordered(ChunkedAlloc::new(), DropOrder::Fifo);
+    /// let _ = rodeo.alloc(String::from("dropped first"));
+    /// let _ = rodeo.alloc(String::from("dropped last"));
+    /// ```
+    #[must_use]
+    pub const fn with_allocator_ordered(allocator: A, drop_order: DropOrder) -> Self {
+        let mut rodeo = Self::with_allocator(allocator);
+        rodeo.drop_order = drop_order;
+        rodeo
+    }
+
+    /// Set the handler called by the infallible methods (e.g.
+    /// [`Rodeo::alloc`]) when allocating fails, instead of panicking.
+    ///
+    /// The handler must diverge, for instance by aborting or by calling a
+    /// platform-specific hook. It is a plain function pointer so that it is
+    /// available without `std`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// fn abort() -> ! {
+    ///     std::process::abort()
+    /// }
+    ///
+    /// let mut rodeo = Rodeo::new();
+    /// rodeo.set_oom_handler(abort);
+    /// let _ = rodeo.alloc(42);
+    /// ```
+    pub fn set_oom_handler(&mut self, handler: fn() -> !) {
+        self.oom_handler = handler;
+    }
+
     /// Return a shared reference to the underlying allocator.
     ///
     /// Any object directly allocated with the allocator
@@ -183,166 +608,2211 @@ impl<A> Rodeo<A> {
     ///
     /// N.B.: there is no direct memory leak, only indirect memory and
     /// resource leaks.
+    ///
+    /// # Panics
+    ///
+    /// In debug builds, panics if some droppable data would be leaked. Use
+    /// [`Rodeo::into_allocator_leaking`] to acknowledge the leak.
     pub fn into_allocator(self) -> A {
+        debug_assert!(
+            self.last.get().is_none(),
+            "droppable data would be leaked, use `into_allocator_leaking` if intended"
+        );
+        self.into_allocator_leaking()
+    }
+
+    /// Convert into the underlying allocator, explicitly leaking any allocated
+    /// droppable data.
+    ///
+    /// ⚠️ No drop will be done on any previous allocation of this Rodeo.
+    ///
+    /// N.B.: there is no direct memory leak, only indirect memory and
+    /// resource leaks.
+    #[allow(clippy::missing_const_for_fn)] // not const with the `fuzz` feature
+    pub fn into_allocator_leaking(self) -> A {
         let alloc = unsafe { ptr::read(&self.allocator) };
+        #[cfg(feature = "fuzz")]
+        drop(unsafe { ptr::read(&self.log) });
         mem::forget(self);
         alloc
     }
-}
 
-impl<A> Rodeo<A>
-where
-    A: ArenaAlloc,
-{
-    /// Allocate an object in this `Rodeo` and return an exclusive reference to
-    /// it.
+    /// Convert into the underlying allocator, reporting whether droppable data
+    /// was leaked.
     ///
-    /// # Panics
+    /// The allocator is always returned. The error variant only signals that
+    /// some values were not dropped, along with their number (slices count
+    /// once), so that callers may detect accidental leaks at runtime.
     ///
-    /// Panics if reserving space for `T` (and possibly an header) fails.
-    pub fn alloc<T: 'static>(&self, value: T) -> &mut T {
-        #[allow(clippy::option_if_let_else)]
-        if let Ok(ref_mut) = self.try_alloc(value) {
-            ref_mut
+    /// # Errors
+    ///
+    /// Returns the allocator and the number of leaked values if some
+    /// finalizers were pending.
+    pub fn try_into_allocator(self) -> Result<A, (A, usize)> {
+        let leaked = self.finalizers.get();
+        let allocator = self.into_allocator_leaking();
+        if leaked == 0 {
+            Ok(allocator)
         } else {
-            oom();
+            Err((allocator, leaked))
         }
     }
 
-    /// Allocate a string slice by copying an input string slice and return
-    /// an exclusive reference to it.
+    /// Discard all the pending finalizers: the values allocated so far will
+    /// never be dropped.
     ///
-    /// # Panics
+    /// The memory itself stays with the allocator, and is freed as usual
+    /// when the arena is dropped. Only the resources owned by the values
+    /// (e.g. heap buffers or file handles) are leaked: files are not closed,
+    /// locks are not released, buffered writers are not flushed. The arena
+    /// remains usable.
     ///
-    /// Panics if reserving space for the slice fails.
-    pub fn alloc_str(&self, value: &str) -> &mut str {
-        #[allow(clippy::option_if_let_else)]
-        if let Ok(ref_mut) = self.try_alloc_str(value) {
-            ref_mut
-        } else {
-            oom();
-        }
+    /// Leaking is safe, so this only needs a shared borrow: the references
+    /// allocated so far stay valid until the arena is dropped. This is meant
+    /// to skip the teardown when it is not needed, e.g., when the process is
+    /// about to exit.
+    ///
+    /// See also [`Rodeo::forget_all`].
+    pub fn leak_all(&self) {
+        self.last.set(None);
+        self.finalizers.set(0);
     }
 
-    /// Allocate a slice by copying the input slice in this `Rodeo` and return
-    /// an exclusive reference to it.
+    /// Drop all the values allocated so far and reclaim the memory of the
+    /// arena for reuse, if the allocator supports it (see
+    /// [`ArenaAlloc::CAN_RESET`]).
     ///
-    /// # Panics
+    /// The exclusive borrow guarantees that no reference to the previous
+    /// allocations is still alive. With an allocator that cannot reset, the
+    /// values are still dropped but the memory is kept until the arena is
+    /// dropped.
     ///
-    /// Panics if reserving space for the slice fails.
-    pub fn alloc_slice_copy<T: Copy + 'static>(&self, value: &[T]) -> &mut [T] {
-        #[allow(clippy::option_if_let_else)]
-        if let Ok(ref_mut) = self.try_alloc_slice_copy(value) {
-            ref_mut
-        } else {
-            oom();
-        }
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let mut rodeo = Rodeo::new();
+    /// for frame in 0..3 {
+    ///     let _ = rodeo.alloc(format!("frame {frame}"));
+    ///     rodeo.reset();
+    /// }
+    /// ```
+    pub fn reset(&mut self) {
+        self.drop_all();
+        self.last_alloc.set(None);
+        self.allocated.set(0);
+
+        #[cfg(feature = "zeroize-on-reset")]
+        self.allocator.zeroize();
+        self.allocator.reset();
+        self.generation.set(self.generation.get() + 1);
     }
 
-    /// Allocate a slice by cloning the input slice and return an exclusive
-    /// reference to it.
+    /// Return the number of times this `Rodeo` was reset (see
+    /// [`Rodeo::reset`]).
     ///
-    /// # Panics
+    /// # Example
     ///
-    /// Panics if reserving space for the slice fails.
-    pub fn alloc_slice_clone<T: Clone + 'static>(&self, value: &[T]) -> &mut [T] {
-        #[allow(clippy::option_if_let_else)]
-        if let Ok(ref_mut) = self.try_alloc_slice_clone(value) {
-            ref_mut
-        } else {
-            oom();
-        }
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let mut rodeo = Rodeo::new();
+    /// assert_eq!(rodeo.generation(), 0);
+    /// rodeo.reset();
+    /// rodeo.reset();
+    /// assert_eq!(rodeo.generation(), 2);
+    /// ```
+    #[must_use]
+    pub fn generation(&self) -> u64 {
+        self.generation.get()
     }
 
-    /// Try to allocate an object in this allocator and return an exclusive
-    /// reference to it.
+    /// Drop all the values allocated so far, in reverse allocation order
+    /// unless configured otherwise (see [`DropOrder`]), without reclaiming
+    /// any memory.
     ///
-    /// # Errors
+    /// This releases early the resources owned by the values (e.g. heap
+    /// buffers or file handles). The memory stays with the allocator, use
+    /// [`Rodeo::reset`] to reuse it as well. The exclusive borrow guarantees
+    /// that no reference to the dropped values is still alive.
     ///
-    /// Errors if reserving space for `T` fails.
-    pub fn try_alloc<T: 'static>(&self, value: T) -> Result<&mut T, A::Error> {
-        let ptr: *mut T = if mem::needs_drop::<T>() {
-            let raw =
-                self.try_alloc_layout_with_finalizer(Layout::new::<T>(), drop_finalizer::<T>, ())?;
-            raw.cast()
-        } else {
-            let layout = Layout::new::<T>();
-            self.allocator.try_alloc_layout(layout)?.cast().as_ptr()
-        };
-        unsafe {
-            ptr.write(value);
-            Ok(&mut *ptr)
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let mut rodeo = Rodeo::new();
+    /// let _ = rodeo.alloc(String::from("dropped early"));
+    /// rodeo.drop_all();
+    /// let _ = rodeo.alloc(String::from("dropped with the arena"));
+    /// ```
+    pub fn drop_all(&mut self) {
+        // only the head of the chain is checked, as checking every header
+        // against the allocator would make dropping quadratic; see
+        // `debug_check_invariants` for a full walk
+        #[cfg(debug_assertions)]
+        if let Some(header) = self.last.get() {
+            self.debug_check_header(header);
         }
-    }
 
-    #[inline]
-    fn try_alloc_layout_with_finalizer<D>(
-        &self,
-        data_layout: Layout,
-        finalizer: unsafe fn(NonNull<u8>),
-        finalizer_data: D,
-    ) -> Result<*mut u8, A::Error> {
-        let header_layout = Layout::new::<Header>();
-        let finalizer_data_layout = Layout::new::<D>();
-        let (hdr_fd_layout, fd_offset) = header_layout.extend(finalizer_data_layout).unwrap();
-        let (full_layout, data_offset) = hdr_fd_layout.extend(data_layout).unwrap();
+        if self.drop_order == DropOrder::Fifo {
+            let mut reversed = None;
+            let mut current = *self.last.get_mut();
+            while let Some(mut header) = current {
+                let header_mut = unsafe { header.as_mut() };
+                current = mem::replace(&mut header_mut.previous, reversed);
+                reversed = Some(header);
+            }
+            *self.last.get_mut() = reversed;
+        }
 
-        // allocate enough for the header and the actual value
-        let ptr = self.allocator.try_alloc_layout(full_layout)?.as_ptr();
+        // each header is unlinked before being finalized, so that a panicking
+        // finalizer leaves the chain consistent
+        #[cfg(all(debug_assertions, feature = "std"))]
+        if self.last.get().is_some() {
+            self.debug_check_thread();
+        }
 
-        let header = Header {
-            previous: self.last.take(),
-            finalizer,
-            #[cfg(debug_assertions)]
-            finalizer_data_layout,
-            #[cfg(debug_assertions)]
-            data_layout,
-        };
+        while let Some(header) = *self.last.get_mut() {
+            *self.last.get_mut() = unsafe { header.as_ref() }.previous;
+            *self.finalizers.get_mut() -= 1;
+            Header::finalize(header);
+        }
+    }
 
-        let header_non_null;
-        let value_ptr;
+    /// Consume the arena without dropping anything: neither the allocated
+    /// values, nor the allocator.
+    ///
+    /// Unlike [`Rodeo::leak_all`], the memory of the arena is leaked too. This
+    /// is meant for programs about to exit, which do not need to clean up.
+    pub fn forget_all(self) {
+        mem::forget(self.into_allocator_leaking());
+    }
 
-        unsafe {
-            #[allow(clippy::cast_ptr_alignment)]
-            let header_ptr = ptr.cast::<Header>();
+    /// Check the internal consistency of the finalizer chain.
+    ///
+    /// Walks the pending finalizers and validates the debugging metadata
+    /// recorded with each of them: every header must lie within the memory
+    /// of the allocator, a single value must have no finalizer data, and a
+    /// slice must have a length whose elements exactly fill its data layout.
+    ///
+    /// Only available in debug builds.
+    ///
+    /// # Panics
+    ///
+    /// Panics if an inconsistency is found, which denotes memory corruption.
+    #[cfg(debug_assertions)]
+    pub fn debug_check_invariants(&self) {
+        let unit_layout = Layout::new::<()>();
+        let len_layout = Layout::new::<usize>();
+        let (_, offset_len) = HEADER_LAYOUT.extend(len_layout).unwrap();
 
-            header_ptr.write(header);
-            header_non_null = NonNull::new_unchecked(header_ptr);
+        let mut current = self.last.get();
+        while let Some(header_ptr) = current {
+            self.debug_check_header(header_ptr);
+            let header = unsafe { header_ptr.as_ref() };
+            let data_layout = header.data_layout;
 
-            let finalizer_data_ptr = ptr.wrapping_add(fd_offset).cast::<D>();
-            finalizer_data_ptr.write(finalizer_data);
+            // replayed allocations (see `Rodeo::replay`) only have zeroed
+            // finalizer data
+            #[cfg(any(test, feature = "fuzz"))]
+            let replayed = same_finalizer(header.finalizer, noop_finalizer);
+            #[cfg(not(any(test, feature = "fuzz")))]
+            let replayed = false;
 
-            value_ptr = ptr.wrapping_add(data_offset);
-        }
+            if replayed {
+                // nothing to check
+            } else if header.finalizer_data_layout == len_layout {
+                let len: usize =
+                    unsafe { *header_ptr.as_ptr().cast::<u8>().add(offset_len).cast() };
+                let size = data_layout.size();
+                let consistent = if len == 0 {
+                    size == 0
+                } else {
+                    size % len == 0 && (size / len) % data_layout.align() == 0
+                };
+                assert!(
+                    consistent,
+                    "corrupted slice header: {len} elements in {data_layout:?}"
+                );
+            } else if header.finalizer_data_layout != Layout::new::<CustomFinalizer>() {
+                assert_eq!(
+                    header.finalizer_data_layout, unit_layout,
+                    "corrupted header: unexpected finalizer data layout"
+                );
+            }
 
-        self.last.set(Some(header_non_null));
+            current = header.previous;
+        }
+    }
 
-        Ok(value_ptr)
+    /// Check that a header of the finalizer chain is properly aligned and
+    /// within the memory of the allocator, before dereferencing it.
+    #[cfg(debug_assertions)]
+    fn debug_check_header(&self, header: NonNull<Header>) {
+        assert!(
+            header.as_ptr() as usize % mem::align_of::<Header>() == 0
+                && self.allocator.contains(header.cast()),
+            "corrupted finalizer chain: invalid header at {header:p}"
+        );
     }
 
-    /// Try to allocate a string slice by copying an input string slice and return
-    /// an exclusive reference to it.
+    /// Return the number of pending finalizers, i.e., of droppable values
+    /// and slices allocated so far and not dropped yet.
+    ///
+    /// Only the allocations that need to be dropped are counted: `Copy`
+    /// values, strings and other non-droppable data are not. A slice counts
+    /// once. This is the same as [`Rodeo::num_pending_finalizers`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let _ = rodeo.alloc(42);
+    /// let _ = rodeo.alloc(String::from("droppable"));
+    /// let _ = rodeo.alloc_slice_clone(&[vec![1], vec![2]]);
+    /// assert_eq!(rodeo.num_allocations(), 2);
+    /// ```
+    #[must_use]
+    pub fn num_allocations(&self) -> usize {
+        self.finalizers.get()
+    }
+
+    /// Return the number of finalizers still pending in the chain.
+    ///
+    /// The count is maintained as the chain evolves, so this is `O(1)`. It
+    /// is handy to check for leaks in tests: values dropped early (e.g. with
+    /// [`Rodeo::drop_all`]) or forgotten (e.g. with [`Rodeo::forget`]) are not
+    /// pending anymore.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let mut rodeo = Rodeo::new();
+    /// let _ = rodeo.alloc(42);
+    /// let _ = rodeo.alloc(String::from("droppable"));
+    /// let _ = rodeo.alloc(vec![1, 2, 3]);
+    /// assert_eq!(rodeo.num_pending_finalizers(), 2);
+    ///
+    /// rodeo.drop_all();
+    /// assert_eq!(rodeo.num_pending_finalizers(), 0);
+    /// ```
+    #[must_use]
+    pub fn num_pending_finalizers(&self) -> usize {
+        self.finalizers.get()
+    }
+
+    /// Return `true` if no finalizer is pending.
+    ///
+    /// Like [`Rodeo::num_allocations`], this only reflects droppable
+    /// allocations: a `Rodeo` holding only `Copy` data reports empty.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.last.get().is_none()
+    }
+
+    /// Return the layout of the last allocation with a pending finalizer, if
+    /// any.
+    ///
+    /// Only the data is accounted for, not the header. For a slice, this is
+    /// the layout of its elements.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::alloc::Layout;
+    ///
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// assert_eq!(rodeo.last_layout(), None);
+    /// let _ = rodeo.alloc(String::new());
+    /// let _ = rodeo.alloc(42_u64);
+    /// assert_eq!(rodeo.last_layout(), Some(Layout::new::<String>()));
+    /// ```
+    #[must_use]
+    pub fn last_layout(&self) -> Option<Layout> {
+        self.pending_layouts().next()
+    }
+
+    /// Iterate over the layouts of the allocations with a pending finalizer,
+    /// from the last one.
+    ///
+    /// Only the headers are read, never the allocated data. This helps to
+    /// audit the composition of the arena.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::alloc::Layout;
+    ///
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let _ = rodeo.alloc(String::new());
+    /// let _ = rodeo.alloc_slice_clone(&[vec![1_u8], vec![2]]);
+    /// let layouts: Vec<_> = rodeo.pending_layouts().collect();
+    /// assert_eq!(
+    ///     layouts,
+    ///     [Layout::new::<[Vec<u8>; 2]>(), Layout::new::<String>()]
+    /// );
+    /// ```
+    pub fn pending_layouts(&self) -> impl Iterator<Item = Layout> + '_ {
+        // the headers are never freed while the arena is borrowed
+        core::iter::successors(self.last.get(), |header| {
+            unsafe { header.as_ref() }.previous
+        })
+        .map(|header| unsafe { header.as_ref() }.data_layout)
+    }
+
+    /// Return the number of bytes requested from the allocator so far,
+    /// headers included.
+    ///
+    /// This counts the logical size of the allocations, not the memory
+    /// reserved by the allocator (e.g. the chunks of a _bumpalo_ `Bump`), nor
+    /// the padding between allocations. Failed allocations are not counted,
+    /// undone ones are deducted, and [`Rodeo::reset`] starts over from zero.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let _ = rodeo.alloc(42_u64);
+    /// assert_eq!(rodeo.allocated_bytes(), 8);
+    /// ```
+    #[must_use]
+    pub fn allocated_bytes(&self) -> usize {
+        self.allocated.get()
+    }
+
+    /// Return a human-readable one-line summary of this `Rodeo`, for logging
+    /// purposes.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let _ = rodeo.alloc(String::from("a"));
+    /// let summary = rodeo.summary().to_string();
+    /// assert!(summary.starts_with("Rodeo: 1 pending finalizer, ~"));
+    /// assert!(summary.contains(" B allocated, ~"));
+    /// ```
+    pub fn summary(&self) -> impl fmt::Display + '_ {
+        struct Summary<'a, A: ArenaAlloc>(&'a Rodeo<A>);
+
+        impl<A: ArenaAlloc> fmt::Display for Summary<'_, A> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                let finalizers = self.0.finalizers.get();
+                let plural = if finalizers == 1 { "" } else { "s" };
+                write!(f, "Rodeo: {finalizers} pending finalizer{plural}, ~")?;
+                fmt_bytes(f, self.0.allocated.get())?;
+                f.write_str(" allocated, ~")?;
+                fmt_bytes(f, finalizers.saturating_mul(HEADER_LAYOUT.size()))?;
+                f.write_str(" overhead")
+            }
+        }
+
+        Summary(self)
+    }
+
+    /// Unbind this `Rodeo` from its thread.
+    ///
+    /// In debug builds with the `std` feature, a `Rodeo` is bound to the
+    /// thread of its first allocation, and allocating or dropping pending
+    /// finalizers from another thread panics. This catches cross-thread
+    /// misuse through raw pointers. If the arena was deliberately transferred
+    /// to another thread, call this method first: the next allocation binds it
+    /// again.
+    ///
+    /// Does nothing in other builds.
+    pub fn release_thread(&mut self) {
+        #[cfg(all(debug_assertions, feature = "std"))]
+        self.thread.set(None);
+    }
+
+    /// Check that this `Rodeo` is used from the thread it is bound to,
+    /// binding it to the current thread if needed.
+    #[cfg(all(debug_assertions, feature = "std"))]
+    fn debug_check_thread(&self) {
+        let current = std::thread::current().id();
+        match self.thread.get() {
+            None => self.thread.set(Some(current)),
+            Some(owner) => assert!(owner == current, "Rodeo used from another thread"),
+        }
+    }
+
+    /// Undo the last allocation of this `Rodeo`: drop it if needed, and
+    /// reclaim its memory.
+    ///
+    /// This is only possible if the allocator can deallocate (see
+    /// [`ArenaAlloc::CAN_DEALLOC`]) and if nothing was allocated since, even
+    /// directly with the allocator. Only a single step can be undone.
+    ///
+    /// Returns `true` if the allocation was undone, `false` otherwise, in
+    /// which case nothing is done.
+    ///
+    /// With the `zeroize-on-reset` feature, the reclaimed memory is
+    /// overwritten with zeros before being handed back to the allocator.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let mut rodeo = Rodeo::new();
+    /// let _ = rodeo.alloc(String::from("speculative"));
+    /// assert!(rodeo.undo_last());
+    /// assert!(!rodeo.undo_last());
+    /// ```
+    pub fn undo_last(&mut self) -> bool {
+        let Some((ptr, layout)) = self.last_alloc.get() else {
+            return false;
+        };
+        if !A::CAN_DEALLOC || !self.allocator.is_last_allocation(ptr, layout) {
+            return false;
+        }
+        self.last_alloc.set(None);
+
+        if let Some(header) = self.last.get().filter(|header| header.cast() == ptr) {
+            // unlink before finalizing, so that the chain stays consistent
+            // even if the finalizer panics
+            self.unlink(None, header);
+            Header::finalize(header);
+        }
+
+        #[cfg(feature = "zeroize-on-reset")]
+        {
+            for i in 0..layout.size() {
+                unsafe { ptr.as_ptr().add(i).write_volatile(0) };
+            }
+            core::sync::atomic::compiler_fence(core::sync::atomic::Ordering::SeqCst);
+        }
+        unsafe { self.allocator.dealloc_last(ptr, layout) };
+        self.allocated
+            .set(self.allocated.get().saturating_sub(layout.size()));
+        true
+    }
+
+    /// Drop early all the values and slices of type `T` allocated in this
+    /// `Rodeo`, in reverse allocation order.
+    ///
+    /// Their finalizers are removed from the chain, other allocations are
+    /// left untouched and will be dropped with the arena. The memory itself is
+    /// not reclaimed.
+    ///
+    /// Allocations are recognized by the address of their finalizer. Since
+    /// the compiler may merge identical functions, values of another type with
+    /// the very same drop code may be dropped early too.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let mut rodeo = Rodeo::new();
+    /// let _ = rodeo.alloc(String::from("dropped early"));
+    /// let _ = rodeo.alloc(vec![1, 2, 3]);
+    /// rodeo.finalize_type::<String>();
+    /// ```
+    pub fn finalize_type<T>(&mut self) {
+        if !mem::needs_drop::<T>() {
+            return;
+        }
+
+        let mut next: Option<NonNull<Header>> = None;
+        let mut current = self.last.get();
+        while let Some(header) = current {
+            let header_ref = unsafe { header.as_ref() };
+            let previous = header_ref.previous;
+            let finalizer = header_ref.finalizer;
+
+            if same_finalizer(finalizer, drop_finalizer::<T>)
+                || same_finalizer(finalizer, slice_drop_finalizer::<T>)
+            {
+                // unlink before finalizing, so that the chain stays consistent
+                // even if the finalizer panics
+                self.unlink(next, header);
+                Header::finalize(header);
+            } else {
+                next = Some(header);
+            }
+
+            current = previous;
+        }
+    }
+
+    /// Cancel the finalizer of a value allocated in this `Rodeo`: the value
+    /// will never be dropped.
+    ///
+    /// Only the resources owned by the value are leaked, its memory stays
+    /// with the arena. The other values are dropped as usual. Values that do
+    /// not need to be dropped are ignored.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` needs to be dropped and the value was not allocated in
+    /// this `Rodeo` as a single value (e.g. by [`Rodeo::alloc`]), or if it was
+    /// already forgotten.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let leaked = rodeo.alloc(String::from("leaked"));
+    /// rodeo.forget(leaked);
+    /// assert_eq!(rodeo.num_allocations(), 0);
+    /// ```
+    pub fn forget<T>(&self, value: &mut T) {
+        if !mem::needs_drop::<T>() {
+            return;
+        }
+
+        let (layout, _) = HEADER_LAYOUT.extend(Layout::new::<()>()).unwrap();
+        let (_, offset_t) = layout.extend(Layout::new::<T>()).unwrap();
+        let data: *mut T = value;
+
+        let mut next: Option<NonNull<Header>> = None;
+        let mut current = self.last.get();
+        while let Some(header) = current {
+            let header_ref = unsafe { header.as_ref() };
+            if header.as_ptr().cast::<u8>().wrapping_add(offset_t) == data.cast() {
+                if same_finalizer(header_ref.finalizer, drop_finalizer::<T>) {
+                    self.unlink(next, header);
+                    return;
+                }
+                break;
+            }
+            next = current;
+            current = header_ref.previous;
+        }
+        panic!("value not found among the pending finalizers");
+    }
+
+    /// Move the elements of a slice allocated in this `Rodeo` out into a
+    /// [`Vec`], so that they may outlive the arena.
+    ///
+    /// The slice finalizer is neutralized: the arena will not drop the
+    /// elements again. The memory of the slice itself is not reclaimed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `T` needs to be dropped and the slice was not allocated in
+    /// this `Rodeo` with a finalizer (e.g. by [`Rodeo::alloc_slice_clone`]), or
+    /// if the slice was already drained.
+    ///
+    /// # Safety
+    ///
+    /// The slice must be an entire slice allocated in this `Rodeo`, and must
+    /// not be used anymore after the call, as its elements are moved out.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let words = rodeo.alloc_slice_clone(&["a".to_string(), "b".to_string()]);
+    /// let words = unsafe { rodeo.drain_slice_to_vec(words) };
+    /// drop(rodeo);
+    /// assert_eq!(words, ["a", "b"]);
+    /// ```
+    pub unsafe fn drain_slice_to_vec<T>(&self, slice: &mut [T]) -> alloc::vec::Vec<T> {
+        let len = slice.len();
+        let data = slice.as_mut_ptr();
+
+        if len == 0 {
+            return alloc::vec::Vec::new();
+        }
+
+        self.neutralize_slice_finalizer(data, len);
+
+        let mut vec = alloc::vec::Vec::with_capacity(len);
+        unsafe {
+            ptr::copy_nonoverlapping(data, vec.as_mut_ptr(), len);
+            vec.set_len(len);
+        }
+        vec
+    }
+
+    /// Find the finalizer of the non-empty slice at `data` and remove it from
+    /// the chain, if `T` needs to be dropped, so that the slice is not dropped
+    /// again.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the slice is not found among the pending finalizers.
+    fn neutralize_slice_finalizer<T>(&self, data: *mut T, len: usize) {
+        if !mem::needs_drop::<T>() {
+            return;
+        }
+
+        let (layout, offset_len) = HEADER_LAYOUT.extend(Layout::new::<usize>()).unwrap();
+        let (_, offset_t) = layout.extend(Layout::new::<T>()).unwrap();
+
+        let mut next: Option<NonNull<Header>> = None;
+        let mut current = self.last.get();
+        while let Some(header) = current {
+            let bytes = header.as_ptr().cast::<u8>();
+            let header_ref = unsafe { header.as_ref() };
+            if bytes.wrapping_add(offset_t) == data.cast() {
+                let stored_len: usize = unsafe { *bytes.add(offset_len).cast() };
+                if same_finalizer(header_ref.finalizer, slice_drop_finalizer::<T>)
+                    && stored_len == len
+                {
+                    self.unlink(next, header);
+                    return;
+                }
+                break;
+            }
+            next = current;
+            current = header_ref.previous;
+        }
+        panic!("slice not found among the pending finalizers");
+    }
+
+    /// Remove a pending header from the chain, given the header linked right
+    /// after it (`None` if it is the last one). Its finalizer is not called.
+    fn unlink(&self, next: Option<NonNull<Header>>, header: NonNull<Header>) {
+        let previous = unsafe { header.as_ref() }.previous;
+        match next {
+            Some(mut next) => unsafe { next.as_mut() }.previous = previous,
+            None => self.last.set(previous),
+        }
+        self.finalizers.set(self.finalizers.get() - 1);
+    }
+}
+
+impl<A> Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Allocate an object in this `Rodeo` and return an exclusive reference to
+    /// it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails.
+    pub fn alloc<T: 'static>(&self, value: T) -> &mut T {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc(value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate an object in this `Rodeo`, initialized in place with the
+    /// result of `f`, and return an exclusive reference to it.
+    ///
+    /// Space is reserved before calling `f`, which gives the compiler a chance
+    /// to build the value directly in the arena rather than on the stack.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails. `f`
+    /// is then not called.
+    pub fn alloc_with<T: 'static, F: FnOnce() -> T>(&self, f: F) -> &mut T {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_with(f) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate an object in this `Rodeo`, initialized in place with the
+    /// result of the fallible `f`, and return an exclusive reference to it,
+    /// or the error of `f`.
+    ///
+    /// If `f` fails, the reserved memory is simply abandoned to the allocator,
+    /// and reclaimed with it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let parsed = rodeo.alloc_try_with(|| "42".parse::<u32>());
+    /// assert_eq!(parsed, Ok(&mut 42));
+    /// assert!(rodeo.alloc_try_with(|| "x".parse::<u32>()).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with the error of `f`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails. `f`
+    /// is then not called.
+    pub fn alloc_try_with<T: 'static, E, F: FnOnce() -> Result<T, E>>(
+        &self,
+        f: F,
+    ) -> Result<&mut T, E> {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(result) = self.try_alloc_try_with(f) {
+            result
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate the default value of `T` in this `Rodeo` and return an
+    /// exclusive reference to it.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let buf: &mut Vec<u8> = rodeo.alloc_default();
+    /// buf.extend_from_slice(b"default");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails.
+    pub fn alloc_default<T: Default + 'static>(&self) -> &mut T {
+        self.alloc_with(T::default)
+    }
+
+    /// Allocate an object in this `Rodeo` and return a shared reference to
+    /// it.
+    ///
+    /// This is [`Rodeo::alloc`] for read-only data, whose reference can then
+    /// be copied freely.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let name = rodeo.alloc_shared(String::from("shared"));
+    /// let names = [name, name];
+    /// assert_eq!(names[0], names[1]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails.
+    pub fn alloc_shared<T: 'static>(&self, value: T) -> &T {
+        self.alloc(value)
+    }
+
+    /// Allocate a slice by copying the input slice in this `Rodeo` and return
+    /// a shared reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_copy_shared<T: Copy + 'static>(&self, value: &[T]) -> &[T] {
+        self.alloc_slice_copy(value)
+    }
+
+    /// Allocate a string slice by copying an input string slice and return
+    /// a shared reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_str_shared(&self, value: &str) -> &str {
+        self.alloc_str(value)
+    }
+
+    /// Register a cleanup closure, run when the arena is dropped.
+    ///
+    /// The closure is allocated in the arena and runs exactly once, in the
+    /// same reverse order as the drop of the values allocated in this
+    /// `Rodeo`.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::cell::Cell;
+    /// use std::rc::Rc;
+    ///
+    /// use rodeo::Rodeo;
+    ///
+    /// let flushed = Rc::new(Cell::new(false));
+    /// let rodeo = Rodeo::new();
+    /// let flushed1 = flushed.clone();
+    /// rodeo.defer(move || flushed1.set(true));
+    /// drop(rodeo);
+    /// assert!(flushed.get());
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the closure and its header fails.
+    pub fn defer<F: FnOnce() + 'static>(&self, f: F) {
+        if self.try_defer(f).is_err() {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a string slice by copying an input string slice and return
+    /// an exclusive reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_str(&self, value: &str) -> &mut str {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_str(value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a string slice by moving the contents of a string in this
+    /// `Rodeo`, and return an exclusive reference to it.
+    ///
+    /// No finalizer is needed, and the heap buffer of the string is freed
+    /// right away.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let name = rodeo.alloc_string(format!("{}-{}", "id", 7));
+    /// assert_eq!(name, "id-7");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_string(&self, value: alloc::string::String) -> &mut str {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_string(value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a string slice by copying bytes that must be valid UTF-8 and
+    /// return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails without allocating if the bytes are not valid UTF-8.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_str_from_utf8(&self, bytes: &[u8]) -> Result<&mut str, Utf8Error> {
+        let string = core::str::from_utf8(bytes)?;
+        Ok(self.alloc_str(string))
+    }
+
+    /// Allocate a slice by copying the input slice in this `Rodeo` and return
+    /// an exclusive reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_copy<T: Copy + 'static>(&self, value: &[T]) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_slice_copy(value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a slice by copying at most `max` elements from the start of
+    /// the input slice in this `Rodeo` and return an exclusive reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_copy_prefix<T: Copy + 'static>(&self, value: &[T], max: usize) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_slice_copy_prefix(value, max) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a slice by concatenating the input slices in this `Rodeo` and
+    /// return an exclusive reference to it.
+    ///
+    /// The pieces are copied one after the other in a single allocation.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let joined = rodeo.alloc_slice_concat(&[&[1, 2][..], &[], &[3]]);
+    /// assert_eq!(joined, &[1, 2, 3]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_concat<T: Copy + 'static>(&self, slices: &[&[T]]) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_slice_concat(slices) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a slice of `len` copies of a value in this `Rodeo` and return
+    /// an exclusive reference to it.
+    ///
+    /// Nothing is allocated if the slice is empty or if `T` is zero-sized.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_fill_copy<T: Copy + 'static>(&self, len: usize, value: T) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_slice_fill_copy(len, value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a slice of `len` clones of a value in this `Rodeo` and return
+    /// an exclusive reference to it.
+    ///
+    /// If a clone panics, the clones made so far are dropped.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_fill_clone<T: Clone + 'static>(&self, len: usize, value: &T) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_slice_fill_clone(len, value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a slice of `len` elements in this `Rodeo`, initialized by
+    /// calling `f` with the index of each element, and return an exclusive
+    /// reference to it.
+    ///
+    /// The finalizer of the slice is only registered once all the elements
+    /// are initialized. If `f` panics, the elements initialized so far are
+    /// dropped and the memory is abandoned to the allocator.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let squares = rodeo.alloc_slice_fill_with(4, |i| i * i);
+    /// assert_eq!(squares, [0, 1, 4, 9]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_fill_with<T: 'static, F>(&self, len: usize, f: F) -> &mut [T]
+    where
+        F: FnMut(usize) -> T,
+    {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_slice_fill_with(len, f) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a packed copy of the input slice in this `Rodeo`, ignoring the
+    /// natural alignment of `T`, and return a pointer to it.
+    ///
+    /// This saves the alignment padding, e.g. for serialization buffers. As
+    /// the copy is possibly unaligned, no reference to it may be created: its
+    /// elements must be accessed with [`ptr::read_unaligned`] and
+    /// [`ptr::write_unaligned`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let _ = rodeo.alloc(1_u8);
+    /// let packed = rodeo.alloc_slice_copy_packed(&[0x1234_u16, 0x5678]);
+    /// let second = unsafe { packed.cast::<u16>().as_ptr().add(1).read_unaligned() };
+    /// assert_eq!(second, 0x5678);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_copy_packed<T: Copy + 'static>(&self, value: &[T]) -> NonNull<[T]> {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ptr) = self.try_alloc_slice_copy_packed(value) {
+            ptr
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a slice by copying the input slice in this `Rodeo`, starting
+    /// on a cache line boundary, and return an exclusive reference to it.
+    ///
+    /// The slice is also padded to a whole number of cache lines, so that it
+    /// does not share any cache line with another allocation, which avoids
+    /// false sharing between slices accessed by different threads.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_copy_cache_aligned<T: Copy + 'static>(&self, value: &[T]) -> &mut [T] {
+        self.alloc_slice_copy_aligned(value, CACHE_LINE_SIZE)
+    }
+
+    /// Allocate a slice by copying the input slice in this `Rodeo`, aligned
+    /// and padded to at least `align` bytes, and return an exclusive reference
+    /// to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two or if reserving space for the
+    /// slice fails.
+    pub fn alloc_slice_copy_aligned<T: Copy + 'static>(
+        &self,
+        value: &[T],
+        align: usize,
+    ) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_slice_copy_aligned(value, align) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a slice by cloning the input slice and return an exclusive
+    /// reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_clone<T: Clone + 'static>(&self, value: &[T]) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_slice_clone(value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a slice by moving the elements of a vector in this `Rodeo`,
+    /// and return an exclusive reference to it.
+    ///
+    /// The elements are moved rather than cloned, and the heap buffer of the
+    /// vector is freed right away, spare capacity included.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let mut names = Vec::with_capacity(16);
+    /// names.push(String::from("a"));
+    /// names.push(String::from("b"));
+    /// let names = rodeo.alloc_vec(names);
+    /// assert_eq!(names, &["a", "b"]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_vec<T: 'static>(&self, vec: alloc::vec::Vec<T>) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_vec(vec) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate room for `capacity` elements, clone the input slice into the
+    /// first ones, and return an exclusive reference to the cloned elements
+    /// along with the number of spare slots after them.
+    ///
+    /// Only the cloned elements are dropped by the finalizer, the spare
+    /// capacity is left uninitialized.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let (words, spare) = rodeo.alloc_slice_clone_with_capacity(&["a".to_string()], 4);
+    /// assert_eq!(words, ["a"]);
+    /// assert_eq!(spare, 3);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is smaller than the length of the input slice, or
+    /// if reserving space for the slice fails.
+    pub fn alloc_slice_clone_with_capacity<T: Clone + 'static>(
+        &self,
+        value: &[T],
+        capacity: usize,
+    ) -> (&mut [T], usize) {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(result) = self.try_alloc_slice_clone_with_capacity(value, capacity) {
+            result
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate an object in this `Rodeo` and return an exclusive reference to
+    /// it, along with a unique id.
+    ///
+    /// Ids are increasing in allocation order, which helps correlating
+    /// allocation sites with later events (e.g., drops) when debugging.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails.
+    pub fn alloc_with_id<T: 'static>(&self, value: T) -> (&mut T, u64) {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut_and_id) = self.try_alloc_with_id(value) {
+            ref_mut_and_id
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate an object in the given region of this `Rodeo` and return an
+    /// exclusive reference to it.
+    ///
+    /// The region is only a hint forwarded to the allocator (see
+    /// [`ArenaAlloc::try_alloc_layout_in_region`]).
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails.
+    pub fn alloc_in_region<T: 'static>(&self, region: usize, value: T) -> &mut T {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_in_region(region, value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a slice by cloning the input slice without its consecutive
+    /// duplicates (like [`Vec::dedup`](alloc::vec::Vec::dedup)) and return an
+    /// exclusive reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_dedup<T: Clone + PartialEq + 'static>(&self, value: &[T]) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_slice_dedup(value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate an array in this `Rodeo` and return an exclusive reference to
+    /// it.
+    ///
+    /// Unlike a slice, the length is kept in the type. If needed, the array is
+    /// dropped as a whole with the arena.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let names: &mut [String; 2] = rodeo.alloc_array([String::from("a"), String::from("b")]);
+    /// names[1].push('c');
+    /// assert_eq!(names, &["a", "bc"]);
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the array (and possibly an header)
+    /// fails.
+    pub fn alloc_array<T: 'static, const N: usize>(&self, values: [T; N]) -> &mut [T; N] {
+        self.alloc(values)
+    }
+
+    /// Allocate an object in this `Rodeo` and return a pinned exclusive
+    /// reference to it.
+    ///
+    /// Arena allocations never move, and the value is dropped with the arena
+    /// (or earlier with [`Rodeo::drop_all`] or [`Rodeo::reset`]), before its
+    /// memory is reused. Dropping the `Rodeo` is what eventually invalidates
+    /// the pin.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::marker::PhantomPinned;
+    ///
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let pinned = unsafe { rodeo.alloc_pin((42, PhantomPinned)) };
+    /// assert_eq!(pinned.0, 42);
+    /// ```
+    ///
+    /// # Safety
+    ///
+    /// The pinning guarantee requires the value to be dropped before its
+    /// memory is invalidated. As such, the pending finalizers of this `Rodeo`
+    /// must not be discarded while its memory is reused or freed later on,
+    /// which rules out:
+    /// - [`Rodeo::leak_all`] (e.g. followed by [`Rodeo::reset`]);
+    /// - [`Rodeo::into_allocator`], [`Rodeo::try_into_allocator`] and
+    ///   [`Rodeo::into_allocator_leaking`], which hand the memory over to the
+    ///   allocator without dropping anything.
+    ///
+    /// Leaking the memory of the allocator as well (e.g. with
+    /// [`Rodeo::forget_all`]) is fine.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails.
+    pub unsafe fn alloc_pin<T: 'static>(&self, value: T) -> Pin<&mut T> {
+        unsafe { Pin::new_unchecked(self.alloc(value)) }
+    }
+
+    /// Allocate an uninitialized array in this `Rodeo` and return an exclusive
+    /// reference to it.
+    ///
+    /// No finalizer is registered: the elements initialized by the caller
+    /// will never be dropped by the arena.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the array fails.
+    pub fn alloc_uninit_array<T, const N: usize>(&self) -> &mut [MaybeUninit<T>; N] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_uninit_array() {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate `N` uninitialized arrays in a single block of this `Rodeo`,
+    /// for structure-of-arrays layouts, and return a pointer to each of them.
+    ///
+    /// The array `i` has `lens[i]` elements of size `elem_sizes[i]`, and is
+    /// aligned on `aligns[i]`. No finalizer is registered.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::mem::{align_of, size_of};
+    ///
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let [xs, flags] = rodeo.alloc_soa(
+    ///     [4, 4],
+    ///     [size_of::<f64>(), size_of::<bool>()],
+    ///     [align_of::<f64>(), align_of::<bool>()],
+    /// );
+    /// let xs = xs.cast::<f64>().as_ptr();
+    /// let flags = flags.cast::<bool>().as_ptr();
+    /// for i in 0..4 {
+    ///     unsafe {
+    ///         xs.add(i).write(i as f64);
+    ///         flags.add(i).write(i % 2 == 0);
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the layout of the block is invalid, or if reserving space for
+    /// it fails.
+    pub fn alloc_soa<const N: usize>(
+        &self,
+        lens: [usize; N],
+        elem_sizes: [usize; N],
+        aligns: [usize; N],
+    ) -> [NonNull<u8>; N] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ptrs) = self.try_alloc_soa(lens, elem_sizes, aligns) {
+            ptrs
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a slice by moving the items of an iterator and return an
+    /// exclusive reference to it.
+    ///
+    /// The iterator must report its exact length, like an array or a `Vec`
+    /// iterator. The items are moved into the arena, not cloned.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails, or if the iterator
+    /// yields fewer items than its reported length. In debug builds, also
+    /// panics if it yields more.
+    pub fn alloc_iter<T: 'static, I>(&self, iter: I) -> &mut [T]
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_iter(iter) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate a slice by moving the items of an iterator and return an
+    /// exclusive reference to it.
+    ///
+    /// # Panics
+    ///
+    /// See [`Rodeo::alloc_iter`].
+    #[deprecated(note = "renamed to `alloc_iter`")]
+    pub fn alloc_slice_from_iter_exact<T: 'static, I>(&self, iter: I) -> &mut [T]
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.alloc_iter(iter)
+    }
+
+    /// Allocate a slice by moving the `Ok` items of an iterator of results,
+    /// and return an exclusive reference to it, or the first error.
+    ///
+    /// The iterator must report its exact length. On the first error, the
+    /// items moved so far are dropped, no finalizer is registered, and the
+    /// reserved memory is abandoned to the allocator.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let parsed = rodeo.alloc_from_results(["1", "2"].map(str::parse::<u32>));
+    /// assert_eq!(parsed, Ok(&mut [1, 2][..]));
+    /// assert!(rodeo.alloc_from_results(["1", "x"].map(str::parse::<u32>)).is_err());
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails with the first error yielded by the iterator.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails, or if the iterator
+    /// yields fewer items than its reported length. In debug builds, also
+    /// panics if it yields more.
+    pub fn alloc_from_results<T: 'static, E, I>(&self, iter: I) -> Result<&mut [T], E>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(result) = self.try_alloc_from_results(iter) {
+            result
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Allocate each value separately, then a slice of exclusive references
+    /// to them, and return an exclusive reference to that slice.
+    ///
+    /// Each value has its own finalizer, if needed. The slice of references
+    /// has none.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let refs = rodeo.alloc_ref_slice(["a", "b"].map(String::from));
+    /// for s in refs.iter_mut() {
+    ///     s.push('!');
+    /// }
+    /// assert_eq!(*refs[1], "b!");
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for a value or for the slice fails.
+    pub fn alloc_ref_slice<T: 'static, I>(&self, values: I) -> &mut [&mut T]
+    where
+        I: IntoIterator<Item = T>,
+    {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_ref_slice(values) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Try to allocate an object in this allocator and return an exclusive
+    /// reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails.
+    pub fn try_alloc<T: 'static>(&self, value: T) -> Result<&mut T, RodeoError<A::Error>> {
+        self.try_alloc_in(None, value)
+    }
+
+    /// Try to allocate an object in this `Rodeo`, initialized in place with
+    /// the result of `f`, and return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails. `f` is then not called.
+    pub fn try_alloc_with<T: 'static, F: FnOnce() -> T>(
+        &self,
+        f: F,
+    ) -> Result<&mut T, RodeoError<A::Error>> {
+        self.try_alloc_with_in(None, f)
+    }
+
+    /// Try to allocate an object in this `Rodeo`, initialized in place with
+    /// the result of the fallible `f`, and return an exclusive reference to
+    /// it, or the error of `f`.
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails. `f` is then not called.
+    /// Otherwise, returns the error of `f`, if any.
+    pub fn try_alloc_try_with<T: 'static, E, F: FnOnce() -> Result<T, E>>(
+        &self,
+        f: F,
+    ) -> Result<Result<&mut T, E>, RodeoError<A::Error>> {
+        self.try_alloc_try_with_in(None, f)
+    }
+
+    /// Try to allocate the default value of `T` in this `Rodeo` and return an
+    /// exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails.
+    pub fn try_alloc_default<T: Default + 'static>(&self) -> Result<&mut T, RodeoError<A::Error>> {
+        self.try_alloc_with(T::default)
+    }
+
+    /// Try to register a cleanup closure, run when the arena is dropped.
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for the closure and its header fails. The
+    /// closure is then dropped without being called.
+    pub fn try_defer<F: FnOnce() + 'static>(&self, f: F) -> Result<(), RodeoError<A::Error>> {
+        self.try_alloc_with(|| Deferred(ManuallyDrop::new(f)))?;
+        Ok(())
+    }
+
+    /// Try to allocate an object in this allocator and return an exclusive
+    /// reference to it, along with a unique id.
+    ///
+    /// Ids are increasing in allocation order. Failed allocations do not
+    /// consume any id.
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails.
+    pub fn try_alloc_with_id<T: 'static>(
+        &self,
+        value: T,
+    ) -> Result<(&mut T, u64), RodeoError<A::Error>> {
+        let ref_mut = self.try_alloc(value)?;
+        let id = self.next_id.get();
+        self.next_id.set(id + 1);
+        Ok((ref_mut, id))
+    }
+
+    /// Try to allocate an object in the given region of this `Rodeo` and
+    /// return an exclusive reference to it.
+    ///
+    /// The region is only a hint forwarded to the allocator (see
+    /// [`ArenaAlloc::try_alloc_layout_in_region`]).
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails.
+    pub fn try_alloc_in_region<T: 'static>(
+        &self,
+        region: usize,
+        value: T,
+    ) -> Result<&mut T, RodeoError<A::Error>> {
+        self.try_alloc_in(Some(region), value)
+    }
+
+    #[inline]
+    fn try_alloc_in<T: 'static>(
+        &self,
+        region: Option<usize>,
+        value: T,
+    ) -> Result<&mut T, RodeoError<A::Error>> {
+        self.try_alloc_with_in(region, || value)
+    }
+
+    /// Reserve space for a `T`, then write the result of `f` into it.
+    ///
+    /// The finalizer is only registered once the value is written, so that a
+    /// panicking `f` simply abandons the memory to the allocator.
+    #[inline]
+    fn try_alloc_with_in<T: 'static, F: FnOnce() -> T>(
+        &self,
+        region: Option<usize>,
+        f: F,
+    ) -> Result<&mut T, RodeoError<A::Error>> {
+        match self.try_alloc_try_with_in(region, || Ok::<_, Infallible>(f()))? {
+            Ok(ref_mut) => Ok(ref_mut),
+            Err(never) => match never {},
+        }
+    }
+
+    /// Reserve space for a `T`, then try to write the result of `f` into it.
+    ///
+    /// If `f` fails, no finalizer is registered and the reserved memory is
+    /// abandoned to the allocator.
+    #[inline]
+    fn try_alloc_try_with_in<T: 'static, E, F: FnOnce() -> Result<T, E>>(
+        &self,
+        region: Option<usize>,
+        f: F,
+    ) -> Result<Result<&mut T, E>, RodeoError<A::Error>> {
+        let layout = Layout::new::<T>();
+        if mem::needs_drop::<T>() {
+            let (header, ptr) =
+                self.reserve_with_finalizer(region, layout, drop_finalizer::<T>, ())?;
+            let ptr: *mut T = ptr.cast();
+            match f() {
+                Ok(value) => unsafe { ptr.write(value) },
+                Err(err) => return Ok(Err(err)),
+            }
+            self.link(header);
+            Ok(Ok(unsafe { &mut *ptr }))
+        } else {
+            let ptr: *mut T = if layout.size() == 0 {
+                self.dangling()
+            } else {
+                self.alloc_layout_in(region, layout)?.cast().as_ptr()
+            };
+            Ok(f().map(|value| unsafe {
+                ptr.write(value);
+                &mut *ptr
+            }))
+        }
+    }
+
+    /// Return a dangling pointer standing for an allocation of a zero-sized
+    /// type that does not need to be dropped, without touching the
+    /// allocator.
+    #[inline]
+    fn dangling<T>(&self) -> *mut T {
+        debug_assert!(mem::size_of::<T>() == 0 && !mem::needs_drop::<T>());
+        // nothing to undo
+        self.last_alloc.set(None);
+        NonNull::dangling().as_ptr()
+    }
+
+    /// Try to allocate raw memory for the given layout in this `Rodeo`.
+    ///
+    /// No finalizer is registered: whatever is written in this memory
+    /// **will not be dropped**. Unlike allocating directly with
+    /// [`Rodeo::allocator`], the allocation is accounted for, e.g. by
+    /// [`Rodeo::allocated_bytes`].
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::alloc::Layout;
+    ///
+    /// use rodeo::Rodeo;
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let ptr = rodeo.try_alloc_layout(Layout::new::<u64>()).unwrap();
+    /// unsafe { ptr.cast::<u64>().as_ptr().write(42) };
+    /// assert_eq!(rodeo.allocated_bytes(), 8);
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving the memory fails.
+    pub fn try_alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, RodeoError<A::Error>> {
+        self.alloc_layout(layout)
+    }
+
+    /// Try to allocate raw memory for the given layout in this `Rodeo`, with
+    /// a custom finalizer, and return a pointer to the memory.
+    ///
+    /// The finalizer is called on the returned pointer when the arena drops
+    /// its values (see [`Rodeo::drop_all`]), along with the other finalizers.
+    /// This is the building block of custom arena-backed containers.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving the memory and its header fails.
+    ///
+    /// # Safety
+    ///
+    /// The finalizer must be sound to call on the returned pointer once. It
+    /// is called whether or not the memory was initialized: the caller must
+    /// initialize the memory as expected by the finalizer before the arena
+    /// may drop its values, i.e., before any unwinding or any call taking an
+    /// exclusive borrow of the arena.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// use std::alloc::Layout;
+    /// use std::ptr::NonNull;
+    ///
+    /// use rodeo::Rodeo;
+    ///
+    /// unsafe fn drop_string(ptr: NonNull<u8>) {
+    ///     unsafe { ptr.cast::<String>().as_ptr().drop_in_place() };
+    /// }
+    ///
+    /// let rodeo = Rodeo::new();
+    /// let ptr = unsafe {
+    ///     rodeo.try_alloc_with_finalizer(Layout::new::<String>(), drop_string)
+    /// }
+    /// .unwrap();
+    /// unsafe { ptr.cast::<String>().as_ptr().write(String::from("custom")) };
+    /// ```
+    pub unsafe fn try_alloc_with_finalizer(
+        &self,
+        layout: Layout,
+        finalizer: unsafe fn(NonNull<u8>),
+    ) -> Result<NonNull<u8>, RodeoError<A::Error>> {
+        let (hdr_fd_layout, _) = HEADER_LAYOUT
+            .extend(Layout::new::<CustomFinalizer>())
+            .map_err(|_| RodeoError::LayoutOverflow)?;
+        let (_, data_offset) = hdr_fd_layout
+            .extend(layout)
+            .map_err(|_| RodeoError::LayoutOverflow)?;
+
+        let custom = CustomFinalizer {
+            finalizer,
+            data_offset,
+        };
+        let (header, ptr) = self.reserve_with_finalizer(None, layout, custom_finalizer, custom)?;
+        self.link(header);
+        Ok(unsafe { NonNull::new_unchecked(ptr) })
+    }
+
+    /// Call the out-of-memory handler (see [`Rodeo::set_oom_handler`]).
+    #[inline(never)]
+    #[cold]
+    fn out_of_memory(&self) -> ! {
+        (self.oom_handler)()
+    }
+
+    /// Allocate raw memory from the allocator, in the given region if any,
+    /// within the limit of the arena.
+    #[inline]
+    #[allow(clippy::option_if_let_else)]
+    fn raw_alloc(
+        &self,
+        region: Option<usize>,
+        layout: Layout,
+    ) -> Result<NonNull<u8>, RodeoError<A::Error>> {
+        #[cfg(all(debug_assertions, feature = "std"))]
+        self.debug_check_thread();
+        if layout.size() > self.max_bytes - self.allocated.get() {
+            return Err(RodeoError::LimitExceeded);
+        }
+        let ptr = match region {
+            Some(region) => self.allocator.try_alloc_layout_in_region(layout, region),
+            None => self.allocator.try_alloc_layout(layout),
+        }?;
+        self.allocated
+            .set(self.allocated.get().saturating_add(layout.size()));
+        self.last_alloc.set(Some((ptr, layout)));
+        Ok(ptr)
+    }
+
+    /// Allocate raw memory without finalizer.
+    #[inline]
+    fn alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, RodeoError<A::Error>> {
+        self.alloc_layout_in(None, layout)
+    }
+
+    #[inline]
+    fn alloc_layout_in(
+        &self,
+        region: Option<usize>,
+        layout: Layout,
+    ) -> Result<NonNull<u8>, RodeoError<A::Error>> {
+        let ptr = self.raw_alloc(region, layout)?;
+        #[cfg(feature = "fuzz")]
+        self.log.borrow_mut().push(fuzz::AllocOp {
+            layout,
+            finalizer_data: None,
+        });
+        Ok(ptr)
+    }
+
+    #[cfg(test)]
+    #[inline]
+    fn try_alloc_layout_with_finalizer_in<D>(
+        &self,
+        region: Option<usize>,
+        data_layout: Layout,
+        finalizer: unsafe fn(NonNull<u8>),
+        finalizer_data: D,
+    ) -> Result<*mut u8, RodeoError<A::Error>> {
+        let (header, value_ptr) =
+            self.reserve_with_finalizer(region, data_layout, finalizer, finalizer_data)?;
+        self.link(header);
+        Ok(value_ptr)
+    }
+
+    /// Allocate memory for an header, its finalizer data and the actual data,
+    /// but without registering the finalizer.
+    ///
+    /// The returned header is not part of the finalizer chain until it is
+    /// passed to [`Rodeo::link`]. Until then, the reserved memory is simply
+    /// abandoned to the allocator if dropped.
+    #[inline]
+    fn reserve_with_finalizer<D>(
+        &self,
+        region: Option<usize>,
+        data_layout: Layout,
+        finalizer: unsafe fn(NonNull<u8>),
+        finalizer_data: D,
+    ) -> Result<(NonNull<Header>, *mut u8), RodeoError<A::Error>> {
+        let (header, finalizer_data_ptr, value_ptr) =
+            self.reserve_header(region, data_layout, finalizer, Layout::new::<D>())?;
+        unsafe { finalizer_data_ptr.cast::<D>().write(finalizer_data) };
+        Ok((header, value_ptr))
+    }
+
+    /// Allocate memory for an header, finalizer data of the given layout and
+    /// the actual data, and write the header only.
+    ///
+    /// Return the header, and pointers to the finalizer data and to the
+    /// actual data.
+    #[inline]
+    fn reserve_header(
+        &self,
+        region: Option<usize>,
+        data_layout: Layout,
+        finalizer: unsafe fn(NonNull<u8>),
+        finalizer_data_layout: Layout,
+    ) -> Result<ReservedHeader, RodeoError<A::Error>> {
+        if self.finalizers.get() >= self.max_finalizers {
+            return Err(RodeoError::TooManyFinalizers);
+        }
+
+        let header_layout = Layout::new::<Header>();
+        let (hdr_fd_layout, fd_offset) = header_layout
+            .extend(finalizer_data_layout)
+            .map_err(|_| RodeoError::LayoutOverflow)?;
+        let (full_layout, data_offset) = hdr_fd_layout
+            .extend(data_layout)
+            .map_err(|_| RodeoError::LayoutOverflow)?;
+
+        // allocate enough for the header and the actual value
+        let ptr = self.raw_alloc(region, full_layout)?.as_ptr();
+        #[cfg(feature = "fuzz")]
+        self.log.borrow_mut().push(fuzz::AllocOp {
+            layout: data_layout,
+            finalizer_data: Some(finalizer_data_layout),
+        });
+
+        let header = Header {
+            previous: None,
+            finalizer,
+            #[cfg(debug_assertions)]
+            finalizer_data_layout,
+            data_layout,
+        };
+
+        let header_non_null;
+
+        unsafe {
+            #[allow(clippy::cast_ptr_alignment)]
+            let header_ptr = ptr.cast::<Header>();
+
+            header_ptr.write(header);
+            header_non_null = NonNull::new_unchecked(header_ptr);
+        }
+
+        Ok((
+            header_non_null,
+            ptr.wrapping_add(fd_offset),
+            ptr.wrapping_add(data_offset),
+        ))
+    }
+
+    /// Register a reserved header at the end of the finalizer chain.
+    ///
+    /// If something was allocated since the header was reserved (e.g. by a
+    /// nested allocation), the last allocation can no longer be undone: its
+    /// header, if any, is not the last one of the chain anymore.
+    #[inline]
+    fn link(&self, mut header: NonNull<Header>) {
+        if self
+            .last_alloc
+            .get()
+            .is_some_and(|(ptr, _)| ptr != header.cast())
+        {
+            self.last_alloc.set(None);
+        }
+        unsafe { header.as_mut() }.previous = self.last.take();
+        self.last.set(Some(header));
+        self.finalizers.set(self.finalizers.get() + 1);
+    }
+
+    /// Try to allocate a slice of `len` elements, each initialized with the
+    /// result of `f` called on its index, in order.
+    ///
+    /// The slice finalizer (if needed) is only registered once all the elements
+    /// are initialized. If `f` panics, the elements initialized so far are
+    /// dropped and the reserved memory is abandoned to the allocator.
+    fn try_alloc_slice_with<T, F>(&self, len: usize, f: F) -> Result<&mut [T], RodeoError<A::Error>>
+    where
+        F: FnMut(usize) -> T,
+    {
+        self.try_alloc_slice_with_capacity(len, len, f)
+    }
+
+    /// Allocate room for `capacity` elements and initialize the first `len`
+    /// ones, which only are dropped by the finalizer.
+    fn try_alloc_slice_with_capacity<T, F>(
+        &self,
+        len: usize,
+        capacity: usize,
+        mut f: F,
+    ) -> Result<&mut [T], RodeoError<A::Error>>
+    where
+        F: FnMut(usize) -> T,
+    {
+        match self
+            .try_alloc_slice_try_with_capacity(len, capacity, |i| Ok::<_, Infallible>(f(i)))?
+        {
+            Ok(slice) => Ok(slice),
+            Err(never) => match never {},
+        }
+    }
+
+    /// Allocate room for `capacity` elements and try to initialize the first
+    /// `len` ones, which only are dropped by the finalizer.
+    ///
+    /// On the first error of `f`, the elements initialized so far are dropped,
+    /// no finalizer is registered and the reserved memory is abandoned to the
+    /// allocator.
+    fn try_alloc_slice_try_with_capacity<T, E, F>(
+        &self,
+        len: usize,
+        capacity: usize,
+        mut f: F,
+    ) -> Result<Result<&mut [T], E>, RodeoError<A::Error>>
+    where
+        F: FnMut(usize) -> Result<T, E>,
+    {
+        debug_assert!(len <= capacity);
+        let layout = Layout::array::<T>(capacity).map_err(|_| RodeoError::LayoutOverflow)?;
+
+        if capacity == 0 {
+            // nothing to undo
+            self.last_alloc.set(None);
+            return Ok(Ok(unsafe {
+                slice::from_raw_parts_mut(NonNull::dangling().as_ptr(), 0)
+            }));
+        }
+
+        let (header, ptr) = if mem::needs_drop::<T>() {
+            let (header, ptr) =
+                self.reserve_with_finalizer(None, layout, slice_drop_finalizer::<T>, len)?;
+            unsafe {
+                // the finalizer only knows about the initialized elements
+                (*header.as_ptr()).data_layout = Layout::array::<T>(len)
+                    .unwrap_or_else(|_| unreachable!("smaller than the capacity"));
+            }
+            (Some(header), ptr.cast::<T>())
+        } else if layout.size() == 0 {
+            (None, self.dangling())
+        } else {
+            (None, self.alloc_layout(layout)?.cast().as_ptr())
+        };
+
+        unsafe {
+            let progress = Cell::new(0);
+            let guard = DropCallback(|| {
+                let to_cleanup = progress.get();
+                ptr::drop_in_place(ptr::slice_from_raw_parts_mut(ptr, to_cleanup));
+            });
+
+            for i in 0..len {
+                match f(i) {
+                    Ok(value) => ptr.wrapping_add(i).write(value),
+                    Err(err) => return Ok(Err(err)),
+                }
+                progress.set(i + 1);
+            }
+
+            mem::forget(guard);
+            if let Some(header) = header {
+                self.link(header);
+            }
+            Ok(Ok(slice::from_raw_parts_mut(ptr, len)))
+        }
+    }
+
+    /// Try to allocate a string slice by copying an input string slice and return
+    /// an exclusive reference to it.
     ///
     /// # Errors
     ///
     /// Fails if reserving space for the slice fails.
     #[inline]
-    pub fn try_alloc_str(&self, string: &str) -> Result<&mut str, A::Error> {
+    pub fn try_alloc_str(&self, string: &str) -> Result<&mut str, RodeoError<A::Error>> {
         let bytes = self.try_alloc_slice_copy(string.as_bytes())?;
         Ok(unsafe { core::str::from_utf8_unchecked_mut(bytes) })
     }
 
+    /// Try to allocate a string slice by moving the contents of a string in
+    /// this `Rodeo`, and return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    #[allow(clippy::needless_pass_by_value)] // the string is consumed on purpose
+    pub fn try_alloc_string(
+        &self,
+        string: alloc::string::String,
+    ) -> Result<&mut str, RodeoError<A::Error>> {
+        self.try_alloc_str(&string)
+    }
+
     /// Try to allocate a slice by copying the input slice in this `Rodeo` and return
     /// an exclusive reference to it.
     ///
     /// # Errors
     ///
     /// Fails if reserving space for the slice fails.
-    pub fn try_alloc_slice_copy<T: Copy>(&self, slice: &[T]) -> Result<&mut [T], A::Error> {
+    pub fn try_alloc_slice_copy<T: Copy>(
+        &self,
+        slice: &[T],
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        self.try_alloc_slice_copy_layout(slice, Layout::for_value(slice))
+    }
+
+    /// Try to allocate a slice by copying at most `max` elements from the
+    /// start of the input slice in this `Rodeo` and return an exclusive
+    /// reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    pub fn try_alloc_slice_copy_prefix<T: Copy>(
+        &self,
+        slice: &[T],
+        max: usize,
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        self.try_alloc_slice_copy(&slice[..slice.len().min(max)])
+    }
+
+    /// Try to allocate a slice by concatenating the input slices in this
+    /// `Rodeo` and return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if the total length overflows or if reserving space for the
+    /// slice fails.
+    pub fn try_alloc_slice_concat<T: Copy>(
+        &self,
+        slices: &[&[T]],
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        let len = slices
+            .iter()
+            .try_fold(0usize, |len, slice| len.checked_add(slice.len()))
+            .ok_or(RodeoError::LayoutOverflow)?;
+        let layout = Layout::array::<T>(len).map_err(|_| RodeoError::LayoutOverflow)?;
+        if layout.size() == 0 {
+            // nothing to undo
+            self.last_alloc.set(None);
+            // SAFETY: no memory is needed, and `T` has no drop glue
+            return Ok(unsafe { slice::from_raw_parts_mut(NonNull::dangling().as_ptr(), len) });
+        }
+
+        let ptr: *mut T = self.alloc_layout(layout)?.cast().as_ptr();
+        let mut offset = 0;
+        for slice in slices {
+            unsafe { ptr::copy_nonoverlapping(slice.as_ptr(), ptr.add(offset), slice.len()) };
+            offset += slice.len();
+        }
+        Ok(unsafe { slice::from_raw_parts_mut(ptr, len) })
+    }
+
+    /// Try to allocate a slice of `len` copies of a value in this `Rodeo` and
+    /// return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    pub fn try_alloc_slice_fill_copy<T: Copy>(
+        &self,
+        len: usize,
+        value: T,
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        if len == 0 || mem::size_of::<T>() == 0 {
+            // nothing to undo
+            self.last_alloc.set(None);
+            // SAFETY: no memory is needed, and `T` has no drop glue
+            return Ok(unsafe { slice::from_raw_parts_mut(NonNull::dangling().as_ptr(), len) });
+        }
+
+        let layout = Layout::array::<T>(len).map_err(|_| RodeoError::LayoutOverflow)?;
+        let ptr: *mut T = self.alloc_layout(layout)?.cast().as_ptr();
+        unsafe {
+            for i in 0..len {
+                ptr.add(i).write(value);
+            }
+            Ok(slice::from_raw_parts_mut(ptr, len))
+        }
+    }
+
+    /// Try to allocate a slice of `len` clones of a value in this `Rodeo` and
+    /// return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    pub fn try_alloc_slice_fill_clone<T: Clone>(
+        &self,
+        len: usize,
+        value: &T,
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        self.try_alloc_slice_with(len, |_| value.clone())
+    }
+
+    /// Try to allocate a slice of `len` elements in this `Rodeo`, initialized
+    /// by calling `f` with the index of each element, and return an exclusive
+    /// reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails. `f` is then not called.
+    pub fn try_alloc_slice_fill_with<T: 'static, F>(
+        &self,
+        len: usize,
+        f: F,
+    ) -> Result<&mut [T], RodeoError<A::Error>>
+    where
+        F: FnMut(usize) -> T,
+    {
+        self.try_alloc_slice_with(len, f)
+    }
+
+    /// Try to allocate a packed copy of the input slice in this `Rodeo`,
+    /// ignoring the natural alignment of `T`, and return a pointer to it.
+    ///
+    /// See [`Rodeo::alloc_slice_copy_packed`].
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    pub fn try_alloc_slice_copy_packed<T: Copy>(
+        &self,
+        slice: &[T],
+    ) -> Result<NonNull<[T]>, RodeoError<A::Error>> {
+        let size = mem::size_of_val(slice);
+        let ptr = if size == 0 {
+            NonNull::dangling()
+        } else {
+            // the size of an existing slice is valid
+            let layout = unsafe { Layout::from_size_align_unchecked(size, 1) };
+            let ptr = self.alloc_layout(layout)?;
+            unsafe { ptr::copy_nonoverlapping(slice.as_ptr().cast(), ptr.as_ptr(), size) };
+            ptr
+        };
+        Ok(NonNull::slice_from_raw_parts(ptr.cast(), slice.len()))
+    }
+
+    /// Try to allocate a slice by copying the input slice in this `Rodeo`,
+    /// aligned and padded to at least `align` bytes, and return an exclusive
+    /// reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `align` is not a power of two.
+    pub fn try_alloc_slice_copy_aligned<T: Copy>(
+        &self,
+        slice: &[T],
+        align: usize,
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        let layout = Layout::for_value(slice)
+            .align_to(align)
+            .expect("alignment must be a power of two")
+            .pad_to_align();
+        self.try_alloc_slice_copy_layout(slice, layout)
+    }
+
+    #[inline]
+    fn try_alloc_slice_copy_layout<T: Copy>(
+        &self,
+        slice: &[T],
+        layout: Layout,
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
         debug_assert!(!mem::needs_drop::<T>());
 
         let len = slice.len();
-        let ptr = self.allocator.try_alloc_layout(Layout::for_value(slice))?;
+        if layout.size() == 0 {
+            // nothing to undo
+            self.last_alloc.set(None);
+            // dangling but non-null and aligned as requested
+            let ptr = layout.align() as *mut T;
+            return Ok(unsafe { slice::from_raw_parts_mut(ptr, len) });
+        }
+
+        let ptr = self.alloc_layout(layout)?;
         let ptr: *mut T = ptr.cast().as_ptr();
 
         unsafe {
@@ -357,44 +2827,294 @@ where
     /// # Errors
     ///
     /// Fails if reserving space for the slice fails.
-    pub fn try_alloc_slice_clone<T: Clone>(&self, slice: &[T]) -> Result<&mut [T], A::Error> {
-        let len = slice.len();
+    pub fn try_alloc_slice_clone<T: Clone>(
+        &self,
+        slice: &[T],
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        self.try_alloc_slice_with(slice.len(), |i| slice[i].clone())
+    }
 
-        if mem::needs_drop::<T>() {
-            let finalizer = slice_drop_finalizer::<T>;
-            let ptr =
-                self.try_alloc_layout_with_finalizer(Layout::for_value(slice), finalizer, len)?;
-            let ptr: *mut T = ptr.cast();
+    /// Try to allocate a slice by moving the elements of a vector in this
+    /// `Rodeo`, and return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails. The vector is then
+    /// dropped along with its elements.
+    pub fn try_alloc_vec<T: 'static>(
+        &self,
+        mut vec: alloc::vec::Vec<T>,
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        let src = vec.as_ptr();
+        let slice = self.try_alloc_slice_with(vec.len(), |i| unsafe { src.add(i).read() })?;
+        // the elements were moved out, only free the buffer
+        unsafe { vec.set_len(0) };
+        Ok(slice)
+    }
 
-            unsafe {
-                let progress = Cell::new(0);
-                let guard = DropCallback(|| {
-                    let to_cleanup = progress.get();
-                    for i in 0..to_cleanup {
-                        ptr.wrapping_add(i).drop_in_place();
-                    }
-                });
-
-                for (i, item) in slice.iter().enumerate() {
-                    ptr.wrapping_add(i).write(item.clone());
-                    progress.set(progress.get() + 1);
-                }
+    /// Try to allocate room for `capacity` elements, clone the input slice into
+    /// the first ones, and return an exclusive reference to the cloned elements
+    /// along with the number of spare slots after them.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `capacity` is smaller than the length of the input slice.
+    pub fn try_alloc_slice_clone_with_capacity<T: Clone>(
+        &self,
+        slice: &[T],
+        capacity: usize,
+    ) -> Result<(&mut [T], usize), RodeoError<A::Error>> {
+        assert!(
+            capacity >= slice.len(),
+            "capacity smaller than the slice length"
+        );
+        let cloned =
+            self.try_alloc_slice_with_capacity(slice.len(), capacity, |i| slice[i].clone())?;
+        Ok((cloned, capacity - slice.len()))
+    }
 
-                mem::forget(guard);
-                Ok(slice::from_raw_parts_mut(ptr, len))
-            }
-        } else {
-            let ptr = self.allocator.try_alloc_layout(Layout::for_value(slice))?;
-            let ptr: *mut T = ptr.cast().as_ptr();
+    /// Try to allocate a slice by cloning the input slice without its
+    /// consecutive duplicates (like [`Vec::dedup`](alloc::vec::Vec::dedup))
+    /// and return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the equality of `T` is not consistent, since the elements are
+    /// compared twice: once to compute the length and once to clone them.
+    pub fn try_alloc_slice_dedup<T: Clone + PartialEq>(
+        &self,
+        slice: &[T],
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        let is_first = |&i: &usize| i == 0 || slice[i - 1] != slice[i];
+        let len = (0..slice.len()).filter(is_first).count();
+        let mut indices = (0..slice.len()).filter(is_first);
+        self.try_alloc_slice_with(len, |_| {
+            let i = indices.next().expect("inconsistent equality");
+            slice[i].clone()
+        })
+    }
 
-            unsafe {
-                for (i, item) in slice.iter().enumerate() {
-                    ptr.wrapping_add(i).write(item.clone());
-                }
-                Ok(slice::from_raw_parts_mut(ptr, len))
-            }
+    /// Try to allocate an array in this `Rodeo` and return an exclusive
+    /// reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the array (and possibly an header) fails.
+    pub fn try_alloc_array<T: 'static, const N: usize>(
+        &self,
+        values: [T; N],
+    ) -> Result<&mut [T; N], RodeoError<A::Error>> {
+        self.try_alloc(values)
+    }
+
+    /// Try to allocate an object in this `Rodeo` and return a pinned
+    /// exclusive reference to it.
+    ///
+    /// # Safety
+    ///
+    /// See [`Rodeo::alloc_pin`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails.
+    pub unsafe fn try_alloc_pin<T: 'static>(
+        &self,
+        value: T,
+    ) -> Result<Pin<&mut T>, RodeoError<A::Error>> {
+        self.try_alloc(value)
+            .map(|ref_mut| unsafe { Pin::new_unchecked(ref_mut) })
+    }
+
+    /// Try to allocate an uninitialized array in this `Rodeo` and return an
+    /// exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the array fails.
+    pub fn try_alloc_uninit_array<T, const N: usize>(
+        &self,
+    ) -> Result<&mut [MaybeUninit<T>; N], RodeoError<A::Error>> {
+        let ptr = self.alloc_layout(Layout::new::<[MaybeUninit<T>; N]>())?;
+        Ok(unsafe { &mut *ptr.cast().as_ptr() })
+    }
+
+    /// Try to allocate `N` uninitialized arrays in a single block of this
+    /// `Rodeo`, and return a pointer to each of them.
+    ///
+    /// See [`Rodeo::alloc_soa`].
+    ///
+    /// # Errors
+    ///
+    /// Fails with [`RodeoError::LayoutOverflow`] if the layout of the block is
+    /// invalid (e.g. an alignment is not a power of two), or if reserving
+    /// space for it fails.
+    pub fn try_alloc_soa<const N: usize>(
+        &self,
+        lens: [usize; N],
+        elem_sizes: [usize; N],
+        aligns: [usize; N],
+    ) -> Result<[NonNull<u8>; N], RodeoError<A::Error>> {
+        let mut layout = Layout::new::<()>();
+        let mut offsets = [0; N];
+        for i in 0..N {
+            let array = elem_sizes[i]
+                .checked_mul(lens[i])
+                .and_then(|size| Layout::from_size_align(size, aligns[i]).ok())
+                .ok_or(RodeoError::LayoutOverflow)?;
+            let (extended, offset) = layout
+                .extend(array)
+                .map_err(|_| RodeoError::LayoutOverflow)?;
+            layout = extended;
+            offsets[i] = offset;
+        }
+
+        let base = self.alloc_layout(layout.pad_to_align())?;
+        Ok(offsets.map(|offset| unsafe { NonNull::new_unchecked(base.as_ptr().add(offset)) }))
+    }
+
+    /// Try to allocate a slice by moving the items of an iterator and return an
+    /// exclusive reference to it.
+    ///
+    /// The iterator must report its exact length.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the iterator yields fewer items than its reported length.
+    /// The items moved so far are then dropped. In debug builds, also panics
+    /// if it yields more.
+    pub fn try_alloc_iter<T: 'static, I>(&self, iter: I) -> Result<&mut [T], RodeoError<A::Error>>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let mut iter = iter.into_iter();
+        let slice = self.try_alloc_slice_with(iter.len(), |_| {
+            iter.next()
+                .expect("iterator yielded fewer items than its reported length")
+        })?;
+        #[cfg(debug_assertions)]
+        assert!(
+            iter.next().is_none(),
+            "iterator yielded more items than its reported length"
+        );
+        Ok(slice)
+    }
+
+    /// Try to allocate a slice by moving the items of an iterator and return an
+    /// exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// See [`Rodeo::try_alloc_iter`].
+    ///
+    /// # Panics
+    ///
+    /// See [`Rodeo::try_alloc_iter`].
+    #[deprecated(note = "renamed to `try_alloc_iter`")]
+    pub fn try_alloc_slice_from_iter_exact<T: 'static, I>(
+        &self,
+        iter: I,
+    ) -> Result<&mut [T], RodeoError<A::Error>>
+    where
+        I: IntoIterator<Item = T>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        self.try_alloc_iter(iter)
+    }
+
+    /// Try to allocate a slice by moving the `Ok` items of an iterator of
+    /// results, and return an exclusive reference to it, or the first error.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails. Otherwise, returns the
+    /// first error yielded by the iterator, if any.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the iterator yields fewer items than its reported length.
+    /// The items moved so far are then dropped. In debug builds, also panics
+    /// if it yields more.
+    pub fn try_alloc_from_results<T: 'static, E, I>(
+        &self,
+        iter: I,
+    ) -> Result<Result<&mut [T], E>, RodeoError<A::Error>>
+    where
+        I: IntoIterator<Item = Result<T, E>>,
+        I::IntoIter: ExactSizeIterator,
+    {
+        let mut iter = iter.into_iter();
+        let len = iter.len();
+        let slice = self.try_alloc_slice_try_with_capacity(len, len, |_| {
+            iter.next()
+                .expect("iterator yielded fewer items than its reported length")
+        })?;
+        #[cfg(debug_assertions)]
+        if slice.is_ok() {
+            assert!(
+                iter.next().is_none(),
+                "iterator yielded more items than its reported length"
+            );
         }
+        Ok(slice)
+    }
+
+    /// Try to allocate each value separately, then a slice of exclusive
+    /// references to them, and return an exclusive reference to that slice.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for a value or for the slice fails. The values
+    /// allocated so far are dropped along with the arena.
+    pub fn try_alloc_ref_slice<T: 'static, I>(
+        &self,
+        values: I,
+    ) -> Result<&mut [&mut T], RodeoError<A::Error>>
+    where
+        I: IntoIterator<Item = T>,
+    {
+        let refs = values
+            .into_iter()
+            .map(|value| self.try_alloc(value))
+            .collect::<Result<alloc::vec::Vec<_>, _>>()?;
+        let mut refs = refs.into_iter();
+        self.try_alloc_slice_with(refs.len(), |_| {
+            refs.next()
+                .unwrap_or_else(|| unreachable!("exact size iterator"))
+        })
+    }
+}
+
+/// Cache line size assumed by [`Rodeo::alloc_slice_copy_cache_aligned`].
+pub const CACHE_LINE_SIZE: usize = 64;
+
+/// Format a byte count with a binary unit.
+fn fmt_bytes(f: &mut fmt::Formatter<'_>, bytes: usize) -> fmt::Result {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+
+    if bytes < 1024 {
+        return write!(f, "{bytes} B");
+    }
+    #[allow(clippy::cast_precision_loss)]
+    let mut value = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while value >= 1024.0 && unit + 1 < UNITS.len() {
+        value /= 1024.0;
+        unit += 1;
     }
+    write!(f, "{value:.1} {}", UNITS[unit])
 }
 
 #[inline(never)]
@@ -403,13 +3123,42 @@ fn oom() -> ! {
     panic!("out of memory")
 }
 
-impl<A> Drop for Rodeo<A> {
-    fn drop(&mut self) {
-        let mut current = self.last.get();
-        while let Some(header) = current {
-            Header::finalize(header);
-            current = unsafe { header.as_ref().previous };
+impl<A> fmt::Debug for Rodeo<A>
+where
+    A: ArenaAlloc + fmt::Debug,
+{
+    /// Format the allocator, the number of pending finalizers and the layout
+    /// of each pending allocation, from the last one.
+    ///
+    /// Only the headers are read, never the allocated data.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        /// Layouts of the pending allocations.
+        struct Pending<'a, A: ArenaAlloc>(&'a Rodeo<A>);
+
+        impl<A: ArenaAlloc> fmt::Debug for Pending<'_, A> {
+            fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+                f.debug_list().entries(self.0.pending_layouts()).finish()
+            }
         }
+
+        let mut debug = f.debug_struct("Rodeo");
+        debug
+            .field("allocator", &self.allocator)
+            .field("finalizers", &self.finalizers.get());
+        debug.field("pending", &Pending(self));
+        debug.finish_non_exhaustive()
+    }
+}
+
+impl<A> Drop for Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    fn drop(&mut self) {
+        self.drop_all();
+
+        #[cfg(feature = "zeroize-on-reset")]
+        self.allocator.zeroize();
     }
 }
 
@@ -420,6 +3169,26 @@ extern "C" {}
 #[doc(hidden)]
 pub const HEADER_LAYOUT: Layout = Layout::new::<Header>();
 
+// the header of each droppable allocation is the main memory overhead, keep it
+// to two words and the data layout (the debugging fields are only there in
+// debug builds)
+#[cfg(not(debug_assertions))]
+const _: () = assert!(
+    HEADER_LAYOUT.size() == mem::size_of::<(usize, usize, Layout)>()
+        && HEADER_LAYOUT.align() == mem::align_of::<(usize, usize, Layout)>(),
+    "Header must stay two words and a layout"
+);
+
+/// One-shot cleanup closure, called when dropped.
+struct Deferred<F: FnOnce()>(ManuallyDrop<F>);
+
+impl<F: FnOnce()> Drop for Deferred<F> {
+    fn drop(&mut self) {
+        let f = unsafe { ManuallyDrop::take(&mut self.0) };
+        f();
+    }
+}
+
 struct DropCallback<F: FnMut()>(F);
 
 impl<F: FnMut()> Drop for DropCallback<F> {
@@ -0,0 +1,133 @@
+//! Deferred initialization of linked nodes.
+
+use core::alloc::Layout;
+use core::marker::PhantomData;
+use core::mem;
+use core::ptr::NonNull;
+
+use crate::{drop_finalizer, ArenaAlloc, Header, Rodeo, RodeoError};
+
+/// Reserved but not yet initialized slot for a value of type `T`.
+///
+/// The address of the future value is known upfront (see
+/// [`NodeSlot::as_ptr`]), so that several nodes can be allocated and wired
+/// together before being initialized. The finalizer of the value is only
+/// registered by [`NodeSlot::init`]: until then, the arena will not drop
+/// anything, and dropping the slot simply abandons the memory to the arena.
+///
+/// Values are dropped in reverse order of initialization, like any other
+/// allocation. As such, a node must not access other nodes in its `Drop`
+/// implementation: they may already be dropped. Deferred initialization only
+/// makes the construction safe, not arbitrary references at drop time.
+///
+/// Created by [`Rodeo::alloc_node_deferred`].
+///
+/// # Example
+///
+/// ```rust
+/// use std::ptr;
+///
+/// use rodeo::Rodeo;
+///
+/// struct Node {
+///     value: String,
+///     prev: *const Node,
+///     next: *const Node,
+/// }
+///
+/// let rodeo = Rodeo::new();
+/// let first = rodeo.alloc_node_deferred::<Node>();
+/// let second = rodeo.alloc_node_deferred::<Node>();
+/// let (first_ptr, second_ptr) = (first.as_ptr(), second.as_ptr());
+///
+/// let first = first.init(Node {
+///     value: "first".into(),
+///     prev: ptr::null(),
+///     next: second_ptr,
+/// });
+/// let _ = second.init(Node {
+///     value: "second".into(),
+///     prev: first_ptr,
+///     next: ptr::null(),
+/// });
+/// assert_eq!(unsafe { &(*first.next).value }, "second");
+/// ```
+#[must_use = "the slot must be initialized to be used"]
+pub struct NodeSlot<'r, T, A: ArenaAlloc> {
+    rodeo: &'r Rodeo<A>,
+    header: Option<NonNull<Header>>,
+    ptr: NonNull<T>,
+    marker: PhantomData<T>,
+}
+
+impl<'r, T, A> NodeSlot<'r, T, A>
+where
+    A: ArenaAlloc,
+{
+    /// Return the address the value will be written to.
+    ///
+    /// The pointer must not be dereferenced before the slot is initialized.
+    #[must_use]
+    pub const fn as_ptr(&self) -> *mut T {
+        self.ptr.as_ptr()
+    }
+
+    /// Initialize the slot with the given value, register its finalizer and
+    /// return an exclusive reference to it.
+    pub fn init(self, value: T) -> &'r mut T {
+        unsafe { self.ptr.as_ptr().write(value) };
+        if let Some(header) = self.header {
+            self.rodeo.link(header);
+        }
+        unsafe { &mut *self.ptr.as_ptr() }
+    }
+}
+
+impl<A> Rodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Reserve space for a value of type `T` in this `Rodeo`, to be
+    /// initialized later.
+    ///
+    /// See [`NodeSlot`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails.
+    pub fn alloc_node_deferred<T: 'static>(&self) -> NodeSlot<'_, T, A> {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(slot) = self.try_alloc_node_deferred() {
+            slot
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Try to reserve space for a value of type `T` in this `Rodeo`, to be
+    /// initialized later.
+    ///
+    /// See [`NodeSlot`].
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails.
+    pub fn try_alloc_node_deferred<T: 'static>(
+        &self,
+    ) -> Result<NodeSlot<'_, T, A>, RodeoError<A::Error>> {
+        let layout = Layout::new::<T>();
+        let (header, ptr) = if mem::needs_drop::<T>() {
+            let (header, ptr) =
+                self.reserve_with_finalizer(None, layout, drop_finalizer::<T>, ())?;
+            (Some(header), ptr)
+        } else {
+            (None, self.alloc_layout(layout)?.as_ptr())
+        };
+        Ok(NodeSlot {
+            rodeo: self,
+            header,
+            ptr: unsafe { NonNull::new_unchecked(ptr.cast()) },
+            marker: PhantomData,
+        })
+    }
+}
@@ -0,0 +1,222 @@
+//! Slab of values with stable keys, backed by Rodeo.
+
+use alloc::vec::Vec;
+use core::alloc::Layout;
+use core::ptr::{self, NonNull};
+
+use crate::{ArenaAlloc, Rodeo, RodeoError};
+
+/// Number of slots per chunk of a [`Slab`].
+const CHUNK_LEN: usize = 64;
+
+/// Stable key of a value in a [`Slab`].
+///
+/// A key is made of the index of a slot and of the generation of the value
+/// in that slot. Once the value is removed, the slot may be reused but the
+/// key will not resolve anymore.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Key {
+    index: usize,
+    generation: u32,
+}
+
+impl Key {
+    /// Return the index of the slot.
+    #[must_use]
+    pub const fn index(self) -> usize {
+        self.index
+    }
+
+    /// Return the generation of the value in the slot.
+    #[must_use]
+    pub const fn generation(self) -> u32 {
+        self.generation
+    }
+}
+
+struct Slot<T> {
+    generation: u32,
+    value: Option<T>,
+}
+
+/// Storage of values addressed by stable keys, for entity-component-system
+/// patterns.
+///
+/// Values are stored in chunks of slots allocated in a [`Rodeo`], which never
+/// move. Unlike plain arena allocations, values can be removed individually:
+/// the slot is then free to be reused, with a new generation so that the keys
+/// of removed values do not resolve to new ones.
+///
+/// The slab owns its values and drops the remaining ones when dropped. The
+/// chunks themselves stay in the arena.
+///
+/// # Example
+///
+/// ```rust
+/// use rodeo::slab::Slab;
+/// use rodeo::Rodeo;
+///
+/// let rodeo = Rodeo::new();
+/// let mut slab = Slab::new(&rodeo);
+/// let player = slab.insert(String::from("player"));
+/// let enemy = slab.insert(String::from("enemy"));
+/// assert_eq!(slab.remove(enemy).as_deref(), Some("enemy"));
+/// assert_eq!(slab.get(enemy), None);
+/// assert_eq!(slab.get(player).unwrap(), "player");
+/// ```
+pub struct Slab<'r, T, A: ArenaAlloc> {
+    rodeo: &'r Rodeo<A>,
+    chunks: Vec<NonNull<Slot<T>>>,
+    /// Number of slots initialized so far.
+    slots: usize,
+    /// Indices of the vacant slots.
+    free: Vec<usize>,
+    len: usize,
+}
+
+impl<'r, T, A> Slab<'r, T, A>
+where
+    A: ArenaAlloc,
+{
+    /// Create a new empty slab allocating its chunks in the given `Rodeo`.
+    #[must_use]
+    pub const fn new(rodeo: &'r Rodeo<A>) -> Self {
+        Self {
+            rodeo,
+            chunks: Vec::new(),
+            slots: 0,
+            free: Vec::new(),
+            len: 0,
+        }
+    }
+
+    /// Return the number of values in the slab.
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.len
+    }
+
+    /// Return `true` if the slab holds no value.
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    /// Insert a value and return its key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for a new chunk fails.
+    pub fn insert(&mut self, value: T) -> Key {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(key) = self.try_insert(value) {
+            key
+        } else {
+            self.rodeo.out_of_memory();
+        }
+    }
+
+    /// Try to insert a value and return its key.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for a new chunk fails.
+    pub fn try_insert(&mut self, value: T) -> Result<Key, RodeoError<A::Error>> {
+        let index = if let Some(index) = self.free.pop() {
+            index
+        } else {
+            if self.slots == self.chunks.len() * CHUNK_LEN {
+                let layout =
+                    Layout::array::<Slot<T>>(CHUNK_LEN).map_err(|_| RodeoError::LayoutOverflow)?;
+                self.chunks.push(self.rodeo.alloc_layout(layout)?.cast());
+            }
+            unsafe {
+                self.slot_ptr(self.slots).write(Slot {
+                    generation: 0,
+                    value: None,
+                });
+            }
+            self.slots += 1;
+            self.slots - 1
+        };
+
+        let slot = unsafe { &mut *self.slot_ptr(index) };
+        slot.value = Some(value);
+        self.len += 1;
+        Ok(Key {
+            index,
+            generation: slot.generation,
+        })
+    }
+
+    /// Return `true` if the key resolves to a value.
+    #[must_use]
+    pub fn contains(&self, key: Key) -> bool {
+        self.get(key).is_some()
+    }
+
+    /// Return a shared reference to the value of the given key, if any.
+    #[must_use]
+    pub fn get(&self, key: Key) -> Option<&T> {
+        let slot = self.slot(key.index)?;
+        if slot.generation == key.generation {
+            slot.value.as_ref()
+        } else {
+            None
+        }
+    }
+
+    /// Return an exclusive reference to the value of the given key, if any.
+    #[must_use]
+    pub fn get_mut(&mut self, key: Key) -> Option<&mut T> {
+        if key.index >= self.slots {
+            return None;
+        }
+        let slot = unsafe { &mut *self.slot_ptr(key.index) };
+        if slot.generation == key.generation {
+            slot.value.as_mut()
+        } else {
+            None
+        }
+    }
+
+    /// Remove the value of the given key, if any, and return it.
+    ///
+    /// The slot is then free to be reused by a later insertion.
+    pub fn remove(&mut self, key: Key) -> Option<T> {
+        if key.index >= self.slots {
+            return None;
+        }
+        let slot = unsafe { &mut *self.slot_ptr(key.index) };
+        if slot.generation != key.generation {
+            return None;
+        }
+        let value = slot.value.take()?;
+        slot.generation = slot.generation.wrapping_add(1);
+        self.free.push(key.index);
+        self.len -= 1;
+        Some(value)
+    }
+
+    fn slot(&self, index: usize) -> Option<&Slot<T>> {
+        (index < self.slots).then(|| unsafe { &*self.slot_ptr(index) })
+    }
+
+    /// Return a pointer to the slot of the given index, whose chunk must be
+    /// allocated.
+    fn slot_ptr(&self, index: usize) -> *mut Slot<T> {
+        let chunk = self.chunks[index / CHUNK_LEN];
+        unsafe { chunk.as_ptr().add(index % CHUNK_LEN) }
+    }
+}
+
+impl<T, A> Drop for Slab<'_, T, A>
+where
+    A: ArenaAlloc,
+{
+    fn drop(&mut self) {
+        for index in 0..self.slots {
+            unsafe { ptr::drop_in_place(self.slot_ptr(index)) };
+        }
+    }
+}
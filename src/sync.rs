@@ -0,0 +1,226 @@
+//! Thread-safe arena on top of Rodeo.
+
+use core::ptr::NonNull;
+use std::sync::{Mutex, PoisonError};
+
+use crate::{ArenaAlloc, Rodeo, RodeoError};
+
+/// Arena that can be shared across threads for concurrent allocation.
+///
+/// A [`Rodeo`] keeps its bookkeeping in plain cells and is neither [`Send`]
+/// nor [`Sync`]. This wrapper serializes the allocations with an internal
+/// [`Mutex`], so that the allocator only needs to be [`Send`] (like
+/// _bumpalo_'s `Bump`, which is not `Sync`). The allocated values must be
+/// `Send` as well, since they are dropped on the thread dropping the arena.
+///
+/// Only the allocations that run no user code under the lock are exposed:
+/// [`SyncRodeo::alloc`], [`SyncRodeo::alloc_str`],
+/// [`SyncRodeo::alloc_slice_copy`] and [`SyncRodeo::alloc_slice_clone`]
+/// (whose elements are cloned before taking the lock), and their `try_`
+/// forms. The other `Rodeo` APIs are deliberately left out:
+/// - the allocations taking a closure or an iterator (e.g. `alloc_with`,
+///   `alloc_slice_fill_with` or `alloc_iter`), which would call back user
+///   code under the lock and deadlock if it allocates in the same arena;
+/// - the builders (e.g. `slice_builder` or `writer`), which borrow the
+///   `Rodeo` across calls;
+/// - the methods requiring exclusive access (e.g. `drop_all`, `reset` or
+///   `undo_last`) and the statistics: use [`SyncRodeo::into_inner`] once the
+///   threads are done.
+///
+/// # Example
+///
+/// ```rust
+/// # #[cfg(feature = "bumpalo")]
+/// # {
+/// use rodeo::sync::SyncRodeo;
+///
+/// let rodeo = SyncRodeo::<rodeo::bumpalo::Bump>::default();
+/// std::thread::scope(|s| {
+///     for i in 0..4 {
+///         let rodeo = &rodeo;
+///         s.spawn(move || {
+///             let _ = rodeo.alloc(format!("thread {i}"));
+///         });
+///     }
+/// });
+/// assert_eq!(rodeo.into_inner().num_allocations(), 4);
+/// # }
+/// ```
+pub struct SyncRodeo<A: ArenaAlloc> {
+    rodeo: Mutex<Rodeo<A>>,
+}
+
+// SAFETY: the arena is only accessed under the lock, and released from the
+// thread after each access. The allocator and the allocated values are
+// `Send`, so they may be used and dropped from any thread.
+#[allow(clippy::non_send_fields_in_send_ty)]
+unsafe impl<A: ArenaAlloc + Send> Send for SyncRodeo<A> {}
+
+// SAFETY: see above, shared access only ever allocates under the lock.
+unsafe impl<A: ArenaAlloc + Send> Sync for SyncRodeo<A> {}
+
+impl<A> Default for SyncRodeo<A>
+where
+    A: ArenaAlloc + Default,
+{
+    fn default() -> Self {
+        Self::with_allocator(A::default())
+    }
+}
+
+impl<A> SyncRodeo<A>
+where
+    A: ArenaAlloc,
+{
+    /// Create a new thread-safe arena based on the given arena allocator.
+    #[must_use]
+    pub const fn with_allocator(allocator: A) -> Self {
+        Self {
+            rodeo: Mutex::new(Rodeo::with_allocator(allocator)),
+        }
+    }
+
+    /// Return the inner `Rodeo`, bound to no thread.
+    #[must_use]
+    pub fn into_inner(self) -> Rodeo<A> {
+        self.rodeo
+            .into_inner()
+            .unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Run `f` on the inner `Rodeo`, under the lock.
+    ///
+    /// The allocations of a `Rodeo` are panic-safe, so a poisoned lock is
+    /// ignored.
+    fn with<R>(&self, f: impl FnOnce(&Rodeo<A>) -> R) -> R {
+        let mut rodeo = self.rodeo.lock().unwrap_or_else(PoisonError::into_inner);
+        rodeo.release_thread();
+        let result = f(&rodeo);
+        rodeo.release_thread();
+        result
+    }
+
+    /// Call the out-of-memory handler of the inner `Rodeo`, outside of the
+    /// lock.
+    #[cold]
+    fn out_of_memory(&self) -> ! {
+        let handler = self.with(|rodeo| rodeo.oom_handler);
+        handler()
+    }
+
+    /// Allocate an object in this arena and return an exclusive reference to
+    /// it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for `T` (and possibly an header) fails.
+    pub fn alloc<T: Send + 'static>(&self, value: T) -> &mut T {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc(value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Try to allocate an object in this arena and return an exclusive
+    /// reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Errors if reserving space for `T` fails. The value is then dropped
+    /// under the lock, so its drop must not allocate in this arena.
+    pub fn try_alloc<T: Send + 'static>(&self, value: T) -> Result<&mut T, RodeoError<A::Error>> {
+        let ptr = self.with(|rodeo| rodeo.try_alloc(value).map(NonNull::from))?;
+        Ok(unsafe { &mut *ptr.as_ptr() })
+    }
+
+    /// Allocate a string slice by copying an input string slice and return
+    /// an exclusive reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_str(&self, value: &str) -> &mut str {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_str(value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Try to allocate a string slice by copying an input string slice and
+    /// return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    pub fn try_alloc_str(&self, value: &str) -> Result<&mut str, RodeoError<A::Error>> {
+        let ptr = self.with(|rodeo| rodeo.try_alloc_str(value).map(NonNull::from))?;
+        Ok(unsafe { &mut *ptr.as_ptr() })
+    }
+
+    /// Allocate a slice by copying the input slice in this arena and return
+    /// an exclusive reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_copy<T: Copy + Send + 'static>(&self, value: &[T]) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_slice_copy(value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Try to allocate a slice by copying the input slice in this arena and
+    /// return an exclusive reference to it.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails.
+    pub fn try_alloc_slice_copy<T: Copy + Send + 'static>(
+        &self,
+        value: &[T],
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        let ptr = self.with(|rodeo| rodeo.try_alloc_slice_copy(value).map(NonNull::from))?;
+        Ok(unsafe { &mut *ptr.as_ptr() })
+    }
+
+    /// Allocate a slice by cloning the input slice in this arena and return
+    /// an exclusive reference to it.
+    ///
+    /// # Panics
+    ///
+    /// Panics if reserving space for the slice fails.
+    pub fn alloc_slice_clone<T: Clone + Send + 'static>(&self, value: &[T]) -> &mut [T] {
+        #[allow(clippy::option_if_let_else)]
+        if let Ok(ref_mut) = self.try_alloc_slice_clone(value) {
+            ref_mut
+        } else {
+            self.out_of_memory();
+        }
+    }
+
+    /// Try to allocate a slice by cloning the input slice in this arena and
+    /// return an exclusive reference to it.
+    ///
+    /// The elements are cloned before taking the lock, so that cloning may
+    /// allocate in this arena too. They are then moved into the arena.
+    ///
+    /// # Errors
+    ///
+    /// Fails if reserving space for the slice fails. The clones are then
+    /// dropped under the lock, so their drop must not allocate in this arena.
+    pub fn try_alloc_slice_clone<T: Clone + Send + 'static>(
+        &self,
+        value: &[T],
+    ) -> Result<&mut [T], RodeoError<A::Error>> {
+        let clones = value.to_vec();
+        let ptr = self.with(|rodeo| rodeo.try_alloc_vec(clones).map(NonNull::from))?;
+        Ok(unsafe { &mut *ptr.as_ptr() })
+    }
+}
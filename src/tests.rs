@@ -1,6 +1,12 @@
 //! Main tests of [`Rodeo`]
 
+// the test harness needs `std` anyway, e.g. to catch panics
+extern crate std;
+
+use alloc::boxed::Box;
 use alloc::rc::Rc;
+use alloc::string::{String, ToString};
+use alloc::vec;
 use alloc::vec::Vec;
 use core::cell::RefCell;
 
@@ -32,14 +38,14 @@ fn test_no_mem() {
 }
 
 #[test]
-#[should_panic]
+#[should_panic(expected = "out of memory")]
 fn test_no_mem_panic() {
     let rodeo = Rodeo::with_allocator(FailingAlloc);
     let _ = rodeo.alloc(42);
 }
 
 #[test]
-#[should_panic]
+#[should_panic(expected = "out of memory")]
 fn test_no_mem_panic_drop() {
     let rodeo = Rodeo::with_allocator(FailingAlloc);
     assert!(rodeo.try_alloc(42).is_err());
@@ -76,7 +82,7 @@ fn test_into_allocator_drop_not_called() {
     let witness1 = witness.clone();
     let rodeo = Rodeo::new();
     let ptr: *mut _ = rodeo.alloc(DropCallback(move || witness1.set(true)));
-    let _alloc = rodeo.into_allocator();
+    let _alloc = rodeo.into_allocator_leaking();
     assert!(!witness.get(), "drop should not be called");
     unsafe {
         ptr.drop_in_place();
@@ -155,10 +161,10 @@ fn test_alloc_slice_clone_drop_leak() {
 
         unsafe {
             // evades the two DCs out of the Rodeo, for final cleanup
-            _dc1 = core::ptr::read(&mut slice[0]);
-            _dc2 = core::ptr::read(&mut slice[1]);
+            _dc1 = core::ptr::read(&slice[0]);
+            _dc2 = core::ptr::read(&slice[1]);
         }
-        let _alloc = rodeo.into_allocator();
+        let _alloc = rodeo.into_allocator_leaking();
     }
     assert_eq!(witness.get(), 0);
     assert_eq!(Rc::strong_count(&witness), 5);
@@ -171,7 +177,7 @@ fn test_alloc_slice_clone_drop() {
     let witness = Rc::new(Cell::new(0));
     let witness1 = witness.clone();
     let dc = DropCallback(move || witness1.set(witness1.get() + 1));
-    let array = [dc.clone(), dc.clone()];
+    let array = [dc.clone(), dc];
     {
         let rodeo = Rodeo::new();
         rodeo.alloc_slice_clone(&array);
@@ -243,6 +249,2791 @@ fn test_drop_should_not_leak() {
     ));
     let _ = rodeo.alloc(vec![b'\xAA'; 50]);
     if option_env!("LEAK").is_some() {
-        let _alloc = rodeo.into_allocator();
+        let _alloc = rodeo.into_allocator_leaking();
+    }
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn test_debug_check_invariants() {
+    let rodeo = Rodeo::new();
+    rodeo.debug_check_invariants();
+
+    let _ = rodeo.alloc(42);
+    let _ = rodeo.alloc(vec![1, 2, 3]);
+    let _ = rodeo.alloc_str("hello");
+    let _ = rodeo.alloc_slice_clone(&[String::from("a"), String::from("b")]);
+    let _ = rodeo.alloc_slice_clone::<String>(&[]);
+    let _ = rodeo.alloc(Box::new(1_u8));
+    rodeo.debug_check_invariants();
+}
+
+#[test]
+#[cfg(debug_assertions)]
+fn test_debug_check_invariants_corrupted() {
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc_slice_clone(&[String::from("a"), String::from("b")]);
+
+    let mut header_ptr = rodeo.last.get().unwrap();
+    let header = unsafe { header_ptr.as_mut() };
+    let original = header.data_layout;
+    header.data_layout = Layout::from_size_align(original.size() + 1, 1).unwrap();
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        rodeo.debug_check_invariants();
+    }));
+
+    // restore the header so that the drop is correct
+    unsafe { header_ptr.as_mut() }.data_layout = original;
+
+    assert!(result.is_err());
+}
+
+#[test]
+#[cfg(feature = "fuzz")]
+fn test_replay_log() {
+    // simple deterministic pseudo-random generator
+    let mut state = 0x2545_F491_4F6C_DD1D_u64;
+    let mut next = move || {
+        state ^= state << 13;
+        state ^= state >> 7;
+        state ^= state << 17;
+        state
+    };
+
+    let rodeo = Rodeo::new();
+    for _ in 0..100 {
+        let len = 1 + (next() % 16) as usize;
+        match next() % 5 {
+            0 => drop(rodeo.alloc(next())),
+            1 => drop(rodeo.alloc(vec![0_u8; len])),
+            2 => drop(rodeo.alloc_slice_copy(&vec![0_u16; len])),
+            3 => drop(rodeo.alloc_slice_clone(&vec![String::new(); len])),
+            _ => unsafe {
+                let layout = Layout::from_size_align(len, 8).unwrap();
+                rodeo.try_alloc_with_finalizer(layout, |_| {}).unwrap();
+            },
+        }
+    }
+    let log = rodeo.replay_log();
+    assert_eq!(log.len(), 100);
+
+    let replayed = Rodeo::new();
+    replayed.replay(&log).unwrap();
+    #[cfg(debug_assertions)]
+    replayed.debug_check_invariants();
+    assert_eq!(replayed.replay_log(), log);
+    assert_eq!(replayed.num_allocations(), rodeo.num_allocations());
+    assert_eq!(
+        replayed.allocated_bytes(),
+        rodeo.allocated_bytes(),
+        "same layouts requested, finalizer data included"
+    );
+}
+
+#[test]
+fn test_alloc_in_region() {
+    struct RegionAlloc(Alloc, RefCell<Vec<Option<usize>>>);
+    impl ArenaAlloc for RegionAlloc {
+        type Error = <Alloc as ArenaAlloc>::Error;
+
+        fn try_alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, Self::Error> {
+            self.1.borrow_mut().push(None);
+            self.0.try_alloc_layout(layout)
+        }
+
+        fn try_alloc_layout_in_region(
+            &self,
+            layout: Layout,
+            region: usize,
+        ) -> Result<NonNull<u8>, Self::Error> {
+            self.1.borrow_mut().push(Some(region));
+            self.0.try_alloc_layout(layout)
+        }
+    }
+
+    let rodeo = Rodeo::with_allocator(RegionAlloc(Alloc::default(), RefCell::default()));
+    assert_eq!(rodeo.alloc_in_region(1, 42), &42);
+    assert_eq!(rodeo.alloc_in_region(0, String::from("zero")), "zero");
+    let _ = rodeo.alloc(String::from("none"));
+    assert_eq!(rodeo.alloc_in_region(2, 2_u8), &2);
+
+    assert_eq!(
+        rodeo.allocator().1.borrow().as_slice(),
+        &[Some(1), Some(0), None, Some(2)]
+    );
+
+    // the default implementation ignores the region
+    let rodeo = Rodeo::new();
+    assert_eq!(rodeo.alloc_in_region(3, String::from("three")), "three");
+}
+
+#[test]
+fn test_alloc_slice_copy_cache_aligned() {
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc(1_u8);
+    let s1 = rodeo.alloc_slice_copy_cache_aligned(&[1_u32, 2, 3]);
+    let s2 = rodeo.alloc_slice_copy_cache_aligned(&[4_u32, 5]);
+    assert_eq!(s1, &[1, 2, 3]);
+    assert_eq!(s2, &[4, 5]);
+
+    let line = |ptr: *const u32| ptr as usize / CACHE_LINE_SIZE;
+    let (p1, p2) = (s1.as_ptr(), s2.as_ptr());
+    assert_eq!(p1 as usize % CACHE_LINE_SIZE, 0);
+    assert_eq!(p2 as usize % CACHE_LINE_SIZE, 0);
+    for a in [p1, p1.wrapping_add(2)] {
+        for b in [p2, p2.wrapping_add(1)] {
+            assert_ne!(line(a), line(b));
+        }
+    }
+
+    let s3 = rodeo.alloc_slice_copy_aligned(b"abc", 128);
+    assert_eq!(s3.as_ptr() as usize % 128, 0);
+}
+
+#[test]
+#[should_panic(expected = "alignment must be a power of two")]
+fn test_alloc_slice_copy_aligned_invalid() {
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc_slice_copy_aligned(b"abc", 3);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic(expected = "droppable data would be leaked")]
+fn test_into_allocator_leak_check() {
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc(String::from("leaked"));
+    let _alloc = rodeo.into_allocator();
+}
+
+#[test]
+fn test_into_allocator_no_drop_data() {
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc(42);
+    let _ = rodeo.alloc_str("not leaked");
+    let _alloc = rodeo.into_allocator();
+}
+
+#[test]
+fn test_alloc_iter() {
+    let witness = Rc::new(Cell::new(0));
+    let make = || {
+        let witness = witness.clone();
+        DropCallback(move || witness.set(witness.get() + 1))
+    };
+    let strings = [String::from("a"), String::from("b"), String::from("c")];
+    {
+        let rodeo = Rodeo::new();
+        let slice = rodeo.alloc_iter(strings);
+        assert_eq!(slice, ["a", "b", "c"]);
+
+        let _ = rodeo.alloc_iter([make(), make(), make()]);
+        assert_eq!(witness.get(), 0);
+    }
+    assert_eq!(witness.get(), 3);
+}
+
+#[test]
+#[allow(deprecated)]
+fn test_alloc_slice_from_iter_exact() {
+    let rodeo = Rodeo::new();
+    assert_eq!(rodeo.alloc_slice_from_iter_exact([1, 2, 3]), [1, 2, 3]);
+    assert_eq!(
+        rodeo
+            .try_alloc_slice_from_iter_exact(["a".to_string()])
+            .unwrap(),
+        ["a"]
+    );
+}
+
+#[test]
+fn test_alloc_iter_short() {
+    struct Liar<I>(I);
+    impl<I: Iterator> Iterator for Liar<I> {
+        type Item = I::Item;
+        fn next(&mut self) -> Option<Self::Item> {
+            self.0.next()
+        }
+    }
+    impl<I: Iterator> ExactSizeIterator for Liar<I> {
+        fn len(&self) -> usize {
+            3
+        }
+    }
+
+    let witness = Rc::new(Cell::new(0));
+    let witness1 = witness.clone();
+    let dc = DropCallback(move || witness1.set(witness1.get() + 1));
+    {
+        let rodeo = Rodeo::new();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = rodeo.alloc_iter(Liar([dc.clone(), dc.clone()].into_iter()));
+        }));
+        assert!(result.is_err());
+        assert_eq!(witness.get(), 2);
+    }
+    // not dropped twice by the arena
+    assert_eq!(witness.get(), 2);
+}
+
+#[test]
+#[cfg(debug_assertions)]
+#[should_panic = "iterator yielded more items than its reported length"]
+fn test_alloc_iter_long() {
+    struct Liar<I>(I);
+    impl<I: Iterator> Iterator for Liar<I> {
+        type Item = I::Item;
+        fn next(&mut self) -> Option<Self::Item> {
+            self.0.next()
+        }
+    }
+    impl<I: Iterator> ExactSizeIterator for Liar<I> {
+        fn len(&self) -> usize {
+            1
+        }
+    }
+
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc_iter(Liar([1, 2].into_iter()));
+}
+
+#[test]
+fn test_alloc_slice_clone_panic() {
+    struct PanicOnClone(Rc<Cell<usize>>, bool);
+    impl Clone for PanicOnClone {
+        fn clone(&self) -> Self {
+            assert!(!self.1, "clone failed");
+            Self(self.0.clone(), self.1)
+        }
+    }
+    impl Drop for PanicOnClone {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let witness = Rc::new(Cell::new(0));
+    let array = [
+        PanicOnClone(witness.clone(), false),
+        PanicOnClone(witness.clone(), false),
+        PanicOnClone(witness.clone(), true),
+    ];
+    {
+        let rodeo = Rodeo::new();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = rodeo.alloc_slice_clone(&array);
+        }));
+        assert!(result.is_err());
+        assert_eq!(witness.get(), 2);
+    }
+    // the two clones are not dropped again by the arena
+    assert_eq!(witness.get(), 2);
+}
+
+#[test]
+fn test_build() {
+    let witness = Rc::new(Cell::new(false));
+    let witness1 = witness.clone();
+    let (rodeo, sum) = Rodeo::<Alloc>::build(|rodeo| {
+        let _ = rodeo.alloc(DropCallback(move || witness1.set(true)));
+        let values = rodeo.alloc_slice_copy(&[1, 2, 3]);
+        values.iter().sum::<i32>()
+    });
+    assert_eq!(sum, 6);
+    assert!(!witness.get());
+    assert_eq!(rodeo.alloc(sum), &6);
+    drop(rodeo);
+    assert!(witness.get());
+}
+
+#[test]
+#[cfg(feature = "bumpalo")]
+fn test_zeroize_bump() {
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc_str("secret password");
+    let _ = rodeo.alloc(0xDEAD_BEEF_u32);
+
+    let mut bump = rodeo.into_allocator();
+    ArenaAlloc::zeroize(&mut bump);
+    for chunk in bump.iter_allocated_chunks() {
+        assert!(!chunk.is_empty());
+        assert!(chunk.iter().all(|byte| unsafe { byte.assume_init() } == 0));
+    }
+}
+
+#[test]
+#[cfg(feature = "zeroize-on-reset")]
+fn test_zeroize_on_drop() {
+    struct Spy(Alloc, Rc<Cell<bool>>);
+    impl ArenaAlloc for Spy {
+        type Error = <Alloc as ArenaAlloc>::Error;
+        fn try_alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, Self::Error> {
+            self.0.try_alloc_layout(layout)
+        }
+        fn zeroize(&mut self) {
+            self.1.set(true);
+            self.0.zeroize();
+        }
+    }
+
+    let zeroized = Rc::new(Cell::new(false));
+    let rodeo = Rodeo::with_allocator(Spy(Alloc::default(), zeroized.clone()));
+    let _ = rodeo.alloc(String::from("secret"));
+    assert!(!zeroized.get());
+    drop(rodeo);
+    assert!(zeroized.get());
+}
+
+#[test]
+#[cfg(feature = "zeroize-on-reset")]
+fn test_zeroize_on_undo() {
+    use crate::fallback::ChunkedAlloc;
+
+    let mut rodeo = Rodeo::with_allocator(ChunkedAlloc::new());
+    let _ = rodeo.alloc(String::from("kept"));
+    let secret = rodeo.alloc_str("secret password");
+    let (ptr, len) = (secret.as_ptr(), secret.len());
+    assert!(rodeo.undo_last());
+    // the chunk is still alive, only handed back to the allocator
+    let bytes = unsafe { slice::from_raw_parts(ptr, len) };
+    assert!(bytes.iter().all(|&byte| byte == 0));
+}
+
+#[test]
+fn test_alloc_str_from_utf8() {
+    let rodeo = Rodeo::new();
+    let s = rodeo.alloc_str_from_utf8("héllo".as_bytes()).unwrap();
+    assert_eq!(s, "héllo");
+    s.make_ascii_uppercase();
+    assert_eq!(s, "HéLLO");
+
+    let error = rodeo.alloc_str_from_utf8(b"ab\xFFcd").unwrap_err();
+    assert_eq!(error.valid_up_to(), 2);
+}
+
+#[test]
+fn test_max_finalizers() {
+    let rodeo = Rodeo::with_max_finalizers(Alloc::default(), 2);
+    let _ = rodeo.alloc(String::from("one"));
+    let _ = rodeo.alloc_slice_clone(&[String::from("two")]);
+    assert_eq!(
+        rodeo.try_alloc(String::from("three")).unwrap_err(),
+        RodeoError::TooManyFinalizers
+    );
+    assert_eq!(
+        rodeo
+            .try_alloc_slice_clone(&[String::from("three")])
+            .unwrap_err(),
+        RodeoError::TooManyFinalizers
+    );
+
+    // non-droppable data is unaffected
+    assert_eq!(rodeo.alloc(3), &3);
+    assert_eq!(rodeo.alloc_str("three"), "three");
+}
+
+#[test]
+fn test_alloc_cstr_array() {
+    let rodeo = Rodeo::new();
+    let array = rodeo.alloc_cstr_array(&["one", "", "three"]).unwrap();
+    assert_eq!(array.len(), 3);
+    let strings: Vec<_> = array
+        .iter()
+        .map(|&ptr| unsafe { core::ffi::CStr::from_ptr(ptr) }.to_str().unwrap())
+        .collect();
+    assert_eq!(strings, ["one", "", "three"]);
+
+    let error = rodeo.alloc_cstr_array(&["ok", "n\0pe"]).unwrap_err();
+    assert_eq!(error.nul_position(), 1);
+}
+
+#[test]
+fn test_alloc_cstr() {
+    let rodeo = Rodeo::new();
+
+    let source = core::ffi::CStr::from_bytes_with_nul(b"hello\0").unwrap();
+    let cstr = rodeo.alloc_cstr(source);
+    assert_eq!(cstr, source);
+    assert_ne!(cstr.as_ptr(), source.as_ptr());
+
+    let cstr = rodeo.alloc_cstr_from_bytes(b"world").unwrap();
+    assert_eq!(cstr.to_bytes_with_nul(), b"world\0");
+    assert_eq!(rodeo.alloc_cstr_from_bytes(b"").unwrap().to_bytes(), b"");
+
+    let err = rodeo.alloc_cstr_from_bytes(b"wor\0ld").unwrap_err();
+    assert_eq!(err.nul_position(), 3);
+
+    let rodeo = Rodeo::with_allocator(FailingAlloc);
+    assert!(rodeo.try_alloc_cstr(source).is_err());
+}
+
+#[test]
+fn test_alloc_slice_dedup() {
+    #[derive(Clone)]
+    struct Counted(u8, Rc<Cell<usize>>);
+    impl PartialEq for Counted {
+        fn eq(&self, other: &Self) -> bool {
+            self.0 == other.0
+        }
+    }
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() + 1);
+        }
+    }
+
+    let witness = Rc::new(Cell::new(0));
+    let values: Vec<_> = [1, 1, 2, 3, 3, 3]
+        .into_iter()
+        .map(|n| Counted(n, witness.clone()))
+        .collect();
+    {
+        let rodeo = Rodeo::new();
+        let slice = rodeo.alloc_slice_dedup(&values);
+        assert_eq!(slice.iter().map(|c| c.0).collect::<Vec<_>>(), [1, 2, 3]);
+        assert_eq!(rodeo.alloc_slice_dedup::<u8>(&[]), &[]);
+        assert_eq!(rodeo.alloc_slice_dedup(&[1, 2, 1]), &[1, 2, 1]);
+    }
+    assert_eq!(witness.get(), 3);
+}
+
+#[test]
+fn test_alloc_with_id() {
+    let rodeo = Rodeo::new();
+    let (n, id0) = rodeo.alloc_with_id(42);
+    assert_eq!(n, &42);
+    let (s, id1) = rodeo.alloc_with_id(String::from("droppable"));
+    assert_eq!(s, "droppable");
+    let _ = rodeo.alloc(1.5);
+    let (v, id2) = rodeo.alloc_with_id(vec![1, 2]);
+    assert_eq!(v, &[1, 2]);
+    let (&mut (), id3) = rodeo.alloc_with_id(());
+    assert!(id0 < id1 && id1 < id2 && id2 < id3);
+
+    let rodeo = Rodeo::with_allocator(FailingAlloc);
+    assert!(rodeo.try_alloc_with_id(1).is_err());
+}
+
+#[test]
+fn test_alloc_slice_copy_prefix() {
+    let rodeo = Rodeo::new();
+    let source = [1, 2, 3, 4];
+    assert_eq!(rodeo.alloc_slice_copy_prefix(&source, 2), &[1, 2]);
+    assert_eq!(rodeo.alloc_slice_copy_prefix(&source, 4), &source);
+    assert_eq!(rodeo.alloc_slice_copy_prefix(&source, 10), &source);
+    assert_eq!(rodeo.alloc_slice_copy_prefix(&source, 0), &[]);
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_intern_slice() {
+    use crate::interner::SliceInterner;
+
+    let rodeo = Rodeo::new();
+    let interner = SliceInterner::new(&rodeo);
+    assert!(interner.is_empty());
+
+    let a = interner.intern_slice(&[1u32, 2, 3]);
+    let b = interner.intern_slice(&[1u32, 2, 3]);
+    let c = interner.intern_slice(&[1u32, 2]);
+    assert!(ptr::eq(a, b));
+    assert!(!ptr::eq(a, c));
+    assert_eq!(c, &[1, 2]);
+    assert_eq!(interner.len(), 2);
+}
+
+/// Exercise the common contract of [`Rodeo`] against the allocator `A`.
+fn run_conformance_tests<A: ArenaAlloc + Default>() {
+    // values of various alignments
+    {
+        let rodeo = Rodeo::with_allocator(A::default());
+        let byte = rodeo.alloc(1u8);
+        let word = rodeo.alloc(2u64);
+        let wide = rodeo.alloc(3u128);
+        assert_eq!((*byte, *word, *wide), (1, 2, 3));
+        assert_eq!((word as *const u64).align_offset(mem::align_of::<u64>()), 0);
+        assert_eq!(
+            (wide as *const u128).align_offset(mem::align_of::<u128>()),
+            0
+        );
+    }
+
+    // slices and strings
+    {
+        let rodeo = Rodeo::with_allocator(A::default());
+        let empty = rodeo.alloc_slice_copy::<u64>(&[]);
+        assert!(empty.is_empty());
+        assert_eq!(empty.as_ptr().align_offset(mem::align_of::<u64>()), 0);
+        assert_eq!(rodeo.alloc_str(""), "");
+        assert!(rodeo.alloc_slice_clone::<String>(&[]).is_empty());
+        assert_eq!(rodeo.alloc_slice_copy(&[1u16, 2, 3]), &[1, 2, 3]);
+        assert_eq!(rodeo.alloc_str("rodeo"), "rodeo");
+        let strings = rodeo.alloc_slice_clone(&[String::from("a"), String::from("b")]);
+        assert_eq!(strings, &["a", "b"]);
+        #[cfg(debug_assertions)]
+        rodeo.debug_check_invariants();
+    }
+
+    // drop order
+    {
+        let order = Rc::new(RefCell::new(Vec::new()));
+        let rodeo = Rodeo::with_allocator(A::default());
+        for i in 0..3 {
+            let order = order.clone();
+            let _ = rodeo.alloc(DropCallback(move || order.borrow_mut().push(i)));
+        }
+        let order1 = order.clone();
+        let _ = rodeo.alloc_slice_clone(&[DropCallback(move || order1.borrow_mut().push(3))]);
+        drop(rodeo);
+        assert_eq!(*order.borrow(), [3, 3, 2, 1, 0]);
+    }
+
+    // panic safety
+    {
+        let count = Rc::new(Cell::new(0));
+        let rodeo = Rodeo::with_allocator(A::default());
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = rodeo.alloc_iter((0..4).map(|i| {
+                assert!(i < 2, "boom");
+                let count = count.clone();
+                DropCallback(move || count.set(count.get() + 1))
+            }));
+        }));
+        assert!(result.is_err());
+        assert_eq!(count.get(), 2);
+        drop(rodeo);
+        assert_eq!(count.get(), 2);
+    }
+}
+
+#[cfg(feature = "bumpalo")]
+#[test]
+fn test_conformance_bump() {
+    run_conformance_tests::<::bumpalo::Bump>();
+}
+
+#[test]
+fn test_conformance_leaking_alloc() {
+    run_conformance_tests::<fallback::LeakingAlloc>();
+}
+
+#[test]
+fn test_conformance_chunked_alloc() {
+    run_conformance_tests::<fallback::ChunkedAlloc>();
+}
+
+#[test]
+fn test_drain_slice_to_vec() {
+    let count = Rc::new(Cell::new(0));
+    let rodeo = Rodeo::new();
+    let count1 = count.clone();
+    let _ = rodeo.alloc(DropCallback(move || count1.set(count1.get() + 1)));
+    let items: Vec<_> = (0..3)
+        .map(|i| {
+            let count = count.clone();
+            (
+                String::from("item"),
+                DropCallback(move || count.set(count.get() + 10 + i)),
+            )
+        })
+        .collect();
+    let slice = rodeo.alloc_iter(items);
+    let _after = rodeo.alloc(String::from("after"));
+
+    let drained = unsafe { rodeo.drain_slice_to_vec(slice) };
+    #[cfg(debug_assertions)]
+    rodeo.debug_check_invariants();
+    drop(rodeo);
+    assert_eq!(count.get(), 1);
+    assert_eq!(drained.len(), 3);
+    assert!(drained.iter().all(|(s, _)| s == "item"));
+    drop(drained);
+    assert_eq!(count.get(), 1 + 10 + 11 + 12);
+}
+
+#[test]
+#[should_panic(expected = "slice not found among the pending finalizers")]
+fn test_drain_slice_to_vec_foreign() {
+    let rodeo = Rodeo::new();
+    let mut strings = [String::from("a")];
+    let _ = unsafe { rodeo.drain_slice_to_vec(&mut strings) };
+}
+
+#[test]
+fn test_alloc_fmt() {
+    struct Flaky(Cell<usize>);
+    impl core::fmt::Display for Flaky {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            self.0.set(self.0.get() + 1);
+            write!(f, "{}", "x".repeat(self.0.get()))
+        }
+    }
+
+    let rodeo = Rodeo::new();
+    assert_eq!(rodeo.alloc_fmt(format_args!("plain")), "plain");
+    assert_eq!(
+        rodeo.alloc_fmt(format_args!("{}+{}={:>3}", 1, 2u8, -3i64)),
+        "1+2= -3"
+    );
+    assert_eq!(rodeo.alloc_fmt(format_args!("{}", "")), "");
+    let name = "macro";
+    assert_eq!(crate::format!(rodeo, "{name}!"), "macro!");
+
+    let flaky = Flaky(Cell::new(0));
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _ = rodeo.alloc_fmt(format_args!("{flaky}"));
+    }));
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_string_builder() {
+    let rodeo = Rodeo::new();
+    let mut builder = rodeo.string_builder();
+    assert!(builder.is_empty());
+
+    let long = "x".repeat(100);
+    let words = ["zero", "", "two", long.as_str(), "four"];
+    for (i, word) in words.iter().enumerate() {
+        assert_eq!(builder.push(word), i);
+    }
+    assert_eq!(builder.len(), words.len());
+    assert_eq!(builder.as_str(), words.concat());
+
+    let (buffer, ranges) = builder.finish();
+    assert_eq!(*buffer, words.concat());
+    for (range, word) in ranges.into_iter().zip(words) {
+        assert_eq!(&buffer[range], word);
+    }
+}
+
+#[test]
+fn test_writer() {
+    use core::fmt::Write;
+
+    let rodeo = Rodeo::new();
+    let mut w = rodeo.writer();
+    assert_eq!(w.as_str(), "");
+    let mut expected = String::new();
+    for i in 0..50 {
+        write!(w, "{i},").unwrap();
+        write!(expected, "{i},").unwrap();
     }
+    w.push_str("end");
+    w.push('é');
+    expected.push_str("endé");
+    assert_eq!(w.as_str(), expected);
+    assert_eq!(*w.into_str(), expected);
+
+    assert_eq!(rodeo.writer().into_str(), "");
+
+    let rodeo = Rodeo::with_allocator(FailingAlloc);
+    assert!(write!(rodeo.writer(), "fails").is_err());
+    assert!(rodeo.writer().try_push('x').is_err());
+}
+
+#[test]
+fn test_string() {
+    use core::fmt::Write;
+
+    let rodeo = Rodeo::new();
+    let mut s: crate::String<'_, Alloc> = rodeo.writer();
+    s.push_str("arena");
+    s.push('-');
+    write!(s, "{}", 42).unwrap();
+    let s: &str = s.into_str();
+    assert_eq!(s, "arena-42");
+}
+
+#[test]
+fn test_alloc_empty_slices() {
+    let rodeo = Rodeo::new();
+
+    let empty = rodeo.alloc_slice_copy::<u32>(&[]);
+    assert_eq!(empty.as_ptr(), NonNull::<u32>::dangling().as_ptr());
+
+    let empty = rodeo.alloc_slice_copy_cache_aligned::<u8>(&[]);
+    assert_eq!(empty.as_ptr().align_offset(CACHE_LINE_SIZE), 0);
+
+    let empty = rodeo.alloc_slice_clone::<String>(&[]);
+    assert_eq!(empty.as_ptr(), NonNull::<String>::dangling().as_ptr());
+    assert!(
+        rodeo.last.get().is_none(),
+        "no finalizer for an empty slice"
+    );
+
+    assert!(unsafe { rodeo.drain_slice_to_vec(empty) }.is_empty());
+}
+
+#[test]
+fn test_finalize_type() {
+    struct T(Rc<RefCell<Vec<&'static str>>>, &'static str);
+    impl Drop for T {
+        fn drop(&mut self) {
+            self.0.borrow_mut().push(self.1);
+        }
+    }
+    struct U {
+        _inner: T,
+    }
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut rodeo = Rodeo::new();
+    let _ = rodeo.alloc(T(log.clone(), "t1"));
+    let _ = rodeo.alloc(U {
+        _inner: T(log.clone(), "u1"),
+    });
+    let _ = rodeo.alloc_iter([T(log.clone(), "t2"), T(log.clone(), "t3")]);
+    let _ = rodeo.alloc(U {
+        _inner: T(log.clone(), "u2"),
+    });
+    let _ = rodeo.alloc(T(log.clone(), "t4"));
+
+    rodeo.finalize_type::<T>();
+    assert_eq!(*log.borrow(), ["t4", "t2", "t3", "t1"]);
+    #[cfg(debug_assertions)]
+    rodeo.debug_check_invariants();
+
+    rodeo.finalize_type::<T>();
+    assert_eq!(log.borrow().len(), 4);
+
+    let _ = rodeo.alloc(T(log.clone(), "t5"));
+    drop(rodeo);
+    assert_eq!(*log.borrow(), ["t4", "t2", "t3", "t1", "t5", "u2", "u1"]);
+}
+
+#[cfg(all(debug_assertions, feature = "std"))]
+#[test]
+fn test_debug_check_thread() {
+    struct Shared<'a>(&'a Rodeo<Alloc>);
+    unsafe impl Send for Shared<'_> {}
+
+    let mut rodeo = Rodeo::new();
+    let _ = rodeo.alloc(1);
+
+    let shared = Shared(&rodeo);
+    let result = std::thread::scope(|scope| {
+        scope
+            .spawn(move || {
+                let shared = shared;
+                let _ = shared.0.alloc(2);
+            })
+            .join()
+    });
+    let message = *result.unwrap_err().downcast::<&str>().unwrap();
+    assert_eq!(message, "Rodeo used from another thread");
+
+    rodeo.release_thread();
+    let shared = Shared(&rodeo);
+    std::thread::scope(|scope| {
+        scope.spawn(move || {
+            let shared = shared;
+            let _ = shared.0.alloc(String::from("moved"));
+        });
+    });
+    rodeo.release_thread();
+    drop(rodeo);
+}
+
+#[test]
+fn test_drain_slice() {
+    let count = Rc::new(Cell::new(0));
+    let rodeo = Rodeo::new();
+    let items: Vec<_> = (0..4)
+        .map(|_| {
+            let count = count.clone();
+            DropCallback(move || count.set(count.get() + 1))
+        })
+        .collect();
+    let slice = rodeo.alloc_iter(items);
+
+    let mut drain = unsafe { rodeo.drain_slice(slice) };
+    assert_eq!(drain.len(), 4);
+    drop(drain.next().unwrap());
+    assert_eq!(count.get(), 1);
+    drop(drain.next_back().unwrap());
+    assert_eq!(count.get(), 2);
+    assert_eq!(drain.len(), 2);
+
+    drop(drain);
+    assert_eq!(count.get(), 4);
+    drop(rodeo);
+    assert_eq!(count.get(), 4);
+}
+
+#[test]
+fn test_alloc_uninit_array() {
+    let rodeo = Rodeo::new();
+    let array = rodeo.alloc_uninit_array::<u32, 4>();
+    for (i, slot) in array.iter_mut().enumerate() {
+        slot.write(u32::try_from(i).unwrap() * 10);
+    }
+    let array: &mut [u32; 4] = unsafe { &mut *(array as *mut [MaybeUninit<u32>; 4]).cast() };
+    assert_eq!(array, &[0, 10, 20, 30]);
+
+    let empty = rodeo.alloc_uninit_array::<String, 0>();
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_summary() {
+    struct Bytes(usize);
+    impl core::fmt::Display for Bytes {
+        fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+            fmt_bytes(f, self.0)
+        }
+    }
+
+    let rodeo = Rodeo::new();
+    assert_eq!(
+        rodeo.summary().to_string(),
+        "Rodeo: 0 pending finalizers, ~0 B allocated, ~0 B overhead"
+    );
+    let _ = rodeo.alloc(String::new());
+    let summary = rodeo.summary().to_string();
+    assert!(
+        summary.starts_with("Rodeo: 1 pending finalizer, ~"),
+        "{summary}"
+    );
+    let allocated = Bytes(rodeo.allocated_bytes()).to_string();
+    assert!(
+        summary.contains(&alloc::format!("~{allocated} allocated")),
+        "{summary}"
+    );
+    for i in 0..100 {
+        let _ = rodeo.alloc(i.to_string());
+    }
+    let summary = rodeo.summary().to_string();
+    assert!(summary.contains("101 pending finalizers"), "{summary}");
+    assert!(summary.ends_with(" overhead"), "{summary}");
+
+    assert_eq!(Bytes(1023).to_string(), "1023 B");
+    assert_eq!(Bytes(1536).to_string(), "1.5 KiB");
+    assert_eq!(Bytes(3 << 20).to_string(), "3.0 MiB");
+}
+
+#[test]
+fn test_growth_policy() {
+    use crate::fallback::{ChunkedAlloc, GrowthPolicy};
+
+    fn chunk_sizes(policy: GrowthPolicy) -> Vec<usize> {
+        let rodeo = Rodeo::with_allocator(ChunkedAlloc::with_policy(64, policy));
+        for i in 0..64_u64 {
+            assert_eq!(rodeo.alloc(i), &i);
+        }
+        // larger than some planned chunks
+        assert_eq!(rodeo.alloc([7_u8; 1000]), &[7; 1000]);
+        rodeo.into_allocator().chunk_sizes()
+    }
+
+    assert_eq!(chunk_sizes(GrowthPolicy::Double), [64, 128, 256, 512, 1024]);
+    assert_eq!(
+        chunk_sizes(GrowthPolicy::Fixed(100)),
+        [64, 100, 100, 100, 100, 100, 1000]
+    );
+    assert_eq!(
+        chunk_sizes(GrowthPolicy::Custom(|size| size + 32)),
+        [64, 96, 128, 160, 192, 1000]
+    );
+}
+
+#[test]
+fn test_chunked_alloc_observer() {
+    use crate::fallback::{AllocEvent, ChunkedAlloc, GrowthPolicy};
+
+    std::thread_local! {
+        static EVENTS: RefCell<Vec<AllocEvent>> = const { RefCell::new(Vec::new()) };
+    }
+
+    let mut alloc = ChunkedAlloc::with_policy(64, GrowthPolicy::Double);
+    alloc.set_observer(|event| EVENTS.with(|events| events.borrow_mut().push(event)));
+    let rodeo = Rodeo::with_allocator(alloc);
+    let _ = rodeo.alloc([0_u8; 48]);
+    let _ = rodeo.alloc([0_u8; 48]);
+
+    let value = Layout::new::<[u8; 48]>();
+    let chunk = |size| AllocEvent::ChunkAlloc(Layout::from_size_align(size, 16).unwrap());
+    EVENTS.with(|events| {
+        assert_eq!(
+            *events.borrow(),
+            [
+                chunk(64),
+                AllocEvent::ValueAlloc(value),
+                chunk(128),
+                AllocEvent::ValueAlloc(value),
+            ]
+        );
+    });
+}
+
+#[test]
+fn test_chunked_alloc_alignment() {
+    #[repr(align(64))]
+    struct Aligned(u8);
+
+    let rodeo = Rodeo::with_allocator(fallback::ChunkedAlloc::with_policy(
+        256,
+        fallback::GrowthPolicy::Double,
+    ));
+    for i in 0..16 {
+        let _ = rodeo.alloc(i);
+        let aligned = rodeo.alloc(Aligned(i));
+        assert_eq!((aligned as *const Aligned).align_offset(64), 0);
+        assert_eq!(aligned.0, i);
+    }
+}
+
+#[test]
+fn test_over_aligned() {
+    #[derive(Clone, Copy)]
+    #[repr(align(64))]
+    struct Aligned(u8);
+
+    /// Record its value when dropped, if properly aligned.
+    #[derive(Clone)]
+    #[repr(align(64))]
+    struct DropAligned(u8, Rc<RefCell<Vec<u8>>>);
+
+    impl Drop for DropAligned {
+        fn drop(&mut self) {
+            if (self as *const Self).align_offset(64) == 0 {
+                self.1.borrow_mut().push(self.0);
+            }
+        }
+    }
+
+    let dropped = Rc::new(RefCell::new(Vec::new()));
+    let rodeo = Rodeo::new();
+    for i in 0..4 {
+        // misalign the arena cursor
+        let _ = rodeo.alloc(i);
+
+        let value = rodeo.alloc(DropAligned(i, dropped.clone()));
+        assert_eq!((value as *const DropAligned).align_offset(64), 0);
+
+        let copies = rodeo.alloc_slice_copy(&[Aligned(i); 3]);
+        assert_eq!(copies.as_ptr().align_offset(64), 0);
+        assert!(copies.iter().all(|copy| copy.0 == i));
+
+        let clones = rodeo.alloc_slice_clone(&[
+            DropAligned(10 + i, dropped.clone()),
+            DropAligned(20 + i, dropped.clone()),
+        ]);
+        assert_eq!(clones.as_ptr().align_offset(64), 0);
+    }
+    // the originals of the clones
+    assert_eq!(dropped.borrow().len(), 8);
+    dropped.borrow_mut().clear();
+
+    drop(rodeo);
+    let mut dropped = dropped.borrow().clone();
+    dropped.sort_unstable();
+    assert_eq!(dropped, [0, 1, 2, 3, 10, 11, 12, 13, 20, 21, 22, 23]);
+}
+
+#[test]
+fn test_zero_sized() {
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Unit;
+
+    #[derive(Clone)]
+    struct DropUnit(Rc<Cell<usize>>);
+
+    impl Drop for DropUnit {
+        fn drop(&mut self) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let rodeo = Rodeo::new();
+    for _ in 0..1_000_000 {
+        assert_eq!(*rodeo.alloc(Unit), Unit);
+    }
+    assert_eq!(rodeo.alloc_slice_copy(&[Unit; 5]).len(), 5);
+    assert_eq!(rodeo.alloc_slice_fill_with(7, |_| Unit).len(), 7);
+    assert_eq!(rodeo.alloc_slice_clone(&[Unit; 3]).len(), 3);
+    assert_eq!(rodeo.allocated_bytes(), 0);
+    assert!(rodeo.is_empty());
+
+    // droppable zero-sized values still need a header
+    let drops = Rc::new(Cell::new(0));
+    let _ = rodeo.alloc(DropUnit(drops.clone()));
+    let _ = rodeo.alloc_slice_clone(&[DropUnit(drops.clone()), DropUnit(drops.clone())]);
+    assert_eq!(drops.get(), 2);
+    assert_eq!(rodeo.num_allocations(), 2);
+    drop(rodeo);
+    assert_eq!(drops.get(), 5);
+}
+
+#[cfg(feature = "bumpalo")]
+#[test]
+fn test_zero_sized_skips_allocator() {
+    let rodeo = Rodeo::with_allocator(::bumpalo::Bump::with_capacity(64));
+    let capacity = rodeo.allocator().chunk_capacity();
+    for _ in 0..1000 {
+        let _unit = rodeo.alloc(());
+        let _ = rodeo.alloc_slice_copy(&[(); 3]);
+        let _ = rodeo.alloc_slice_fill_with(3, |_| ());
+    }
+    assert_eq!(rodeo.allocator().chunk_capacity(), capacity);
+}
+
+#[test]
+fn test_alloc_pin() {
+    struct SelfRef {
+        value: u32,
+        this: *const Self,
+        _pinned: core::marker::PhantomPinned,
+    }
+
+    impl Drop for SelfRef {
+        fn drop(&mut self) {
+            // the value has not moved since it was pinned
+            assert!(ptr::eq(self.this, self));
+        }
+    }
+
+    let rodeo = Rodeo::new();
+    let mut pinned = unsafe {
+        rodeo.alloc_pin(SelfRef {
+            value: 1,
+            this: ptr::null(),
+            _pinned: core::marker::PhantomPinned,
+        })
+    };
+    unsafe { pinned.as_mut().get_unchecked_mut() }.this = &*pinned;
+    assert_eq!(pinned.value, 1);
+
+    let pinned = unsafe { rodeo.try_alloc_pin(String::from("pin")) }.unwrap();
+    assert_eq!(&*pinned, "pin");
+    assert_eq!(rodeo.num_allocations(), 2);
+}
+
+#[test]
+fn test_alloc_string() {
+    let rodeo = Rodeo::new();
+    let mut string = String::with_capacity(64);
+    string.push_str("moved");
+    let moved = rodeo.alloc_string(string);
+    moved.make_ascii_uppercase();
+    assert_eq!(moved, "MOVED");
+    assert_eq!(rodeo.alloc_string(String::new()), "");
+    assert!(rodeo.is_empty());
+    assert_eq!(rodeo.allocated_bytes(), 5, "spare capacity is not kept");
+}
+
+#[test]
+fn test_alloc_try_with() {
+    let dropped = Rc::new(Cell::new(false));
+    let rodeo = Rodeo::new();
+
+    let ok = rodeo.alloc_try_with(|| Ok::<_, ()>(String::from("ok")));
+    assert_eq!(ok.map(|s| s.as_str()), Ok("ok"));
+    assert_eq!(rodeo.num_allocations(), 1);
+
+    let failed = rodeo.alloc_try_with(|| Err::<String, _>("oops"));
+    assert_eq!(failed, Err("oops"));
+    let failed = rodeo.alloc_try_with(|| Err::<u32, _>("oops"));
+    assert_eq!(failed, Err("oops"));
+    assert_eq!(rodeo.num_allocations(), 1, "no finalizer registered");
+
+    let dropped1 = dropped.clone();
+    let _ = rodeo.alloc_try_with(|| Ok::<_, ()>(DropCallback(move || dropped1.set(true))));
+    drop(rodeo);
+    assert!(dropped.get());
+
+    let rodeo = Rodeo::with_allocator(FailingAlloc);
+    let result = rodeo.try_alloc_try_with(|| -> Result<u32, ()> { unreachable!() });
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_alloc_from_results() {
+    let drops = Rc::new(Cell::new(0));
+    let counter = {
+        let drops = drops.clone();
+        move || {
+            let drops = drops.clone();
+            DropCallback(move || drops.set(drops.get() + 1))
+        }
+    };
+
+    let rodeo = Rodeo::new();
+    let ok = rodeo.alloc_from_results([Ok::<_, ()>(1_u32), Ok(2)]);
+    assert_eq!(ok, Ok(&mut [1, 2][..]));
+    let empty = rodeo.alloc_from_results(core::iter::empty::<Result<String, ()>>());
+    assert_eq!(empty.map(|slice| slice.len()), Ok(0));
+    assert!(rodeo.is_empty());
+
+    let failed = rodeo.alloc_from_results([Ok(counter()), Err("oops"), Ok(counter())]);
+    assert!(matches!(failed, Err("oops")));
+    assert_eq!(drops.get(), 2, "both the moved and the pending items");
+    assert!(rodeo.is_empty(), "no finalizer registered");
+
+    let slice = rodeo.alloc_from_results([Ok::<_, ()>(counter()), Ok(counter())]);
+    assert_eq!(slice.map(|slice| slice.len()), Ok(2));
+    assert_eq!(rodeo.num_allocations(), 1);
+    drop(rodeo);
+    assert_eq!(drops.get(), 4);
+
+    let rodeo = Rodeo::with_allocator(FailingAlloc);
+    assert!(rodeo
+        .try_alloc_from_results([Ok::<_, ()>(counter())])
+        .is_err());
+}
+
+#[test]
+fn test_alloc_vec() {
+    let drops = Rc::new(Cell::new(0));
+    let counter = {
+        let drops = drops.clone();
+        DropCallback(move || drops.set(drops.get() + 1))
+    };
+
+    let rodeo = Rodeo::new();
+    let mut vec = Vec::with_capacity(8);
+    vec.extend([1_u32, 2, 3]);
+    assert_eq!(rodeo.alloc_vec(vec), &[1, 2, 3]);
+    assert!(rodeo.alloc_vec(Vec::<String>::new()).is_empty());
+    assert!(rodeo.is_empty());
+
+    let moved = rodeo.alloc_vec(vec![counter.clone(), counter.clone(), counter]);
+    assert_eq!(moved.len(), 3);
+    assert_eq!(drops.get(), 0, "moved, not cloned nor dropped");
+    assert_eq!(rodeo.num_allocations(), 1);
+    drop(rodeo);
+    assert_eq!(drops.get(), 3);
+
+    let rodeo = Rodeo::with_allocator(FailingAlloc);
+    let drops = Rc::new(Cell::new(0));
+    let drops1 = drops.clone();
+    let result = rodeo.try_alloc_vec(vec![DropCallback(move || drops1.set(drops1.get() + 1))]);
+    assert!(result.is_err());
+    assert_eq!(drops.get(), 1, "dropped with the vector");
+}
+
+#[test]
+fn test_alloc_array() {
+    let drops = Rc::new(Cell::new(0));
+    let counter = {
+        let drops = drops.clone();
+        DropCallback(move || drops.set(drops.get() + 1))
+    };
+
+    let rodeo = Rodeo::new();
+    let copies = rodeo.alloc_array([1_u32, 2, 3]);
+    copies[2] = 4;
+    assert_eq!(copies, &[1, 2, 4]);
+    assert_eq!(rodeo.alloc_array::<u8, 0>([]), &[]);
+    assert!(rodeo.is_empty());
+
+    let callbacks = rodeo.alloc_array([counter.clone(), counter.clone(), counter]);
+    assert_eq!(callbacks.len(), 3);
+    assert_eq!(rodeo.num_allocations(), 1);
+    drop(rodeo);
+    assert_eq!(drops.get(), 3);
+
+    let rodeo = Rodeo::with_allocator(FailingAlloc);
+    assert!(rodeo.try_alloc_array([1, 2]).is_err());
+}
+
+#[test]
+fn test_with_limit() {
+    let rodeo = Rodeo::with_limit(Alloc::default(), 64);
+    let _ = rodeo.alloc([0_u8; 32]);
+    let _ = rodeo.alloc_slice_copy(&[0_u8; 32]);
+    assert_eq!(rodeo.allocated_bytes(), 64);
+    assert_eq!(
+        rodeo.try_alloc(0_u8).unwrap_err(),
+        RodeoError::LimitExceeded
+    );
+    assert_eq!(
+        rodeo.try_alloc_slice_copy(&[0_u8]).unwrap_err(),
+        RodeoError::LimitExceeded
+    );
+    // zero-sized values do not consume any budget
+    let _unit = rodeo.alloc(());
+
+    // no header is registered past the limit
+    let mut rodeo = Rodeo::with_limit(Alloc::default(), 64);
+    assert_eq!(
+        rodeo.try_alloc([0_u64; 8]).map(|_| ()),
+        Ok(()),
+        "no header needed"
+    );
+    let dropped = Rc::new(Cell::new(false));
+    let dropped1 = dropped.clone();
+    let result = rodeo.try_alloc(DropCallback(move || dropped1.set(true)));
+    assert!(matches!(result, Err(RodeoError::LimitExceeded)));
+    assert!(dropped.get());
+    assert!(rodeo.is_empty());
+    #[cfg(debug_assertions)]
+    rodeo.debug_check_invariants();
+
+    // the budget is replenished by a reset
+    rodeo.reset();
+    assert_eq!(rodeo.allocated_bytes(), 0);
+    assert!(rodeo.try_alloc([0_u64; 8]).is_ok());
+}
+
+#[test]
+#[should_panic = "out of memory"]
+fn test_with_limit_panics() {
+    let rodeo = Rodeo::with_limit(Alloc::default(), 8);
+    let _ = rodeo.alloc(0_u64);
+    let _ = rodeo.alloc(0_u8);
+}
+
+#[test]
+#[should_panic = "custom oom handler"]
+fn test_set_oom_handler() {
+    fn handler() -> ! {
+        panic!("custom oom handler")
+    }
+
+    let mut rodeo = Rodeo::with_limit(Alloc::default(), 0);
+    rodeo.set_oom_handler(handler);
+    let _ = rodeo.alloc(0_u8);
+}
+
+#[test]
+fn test_alloc_node_deferred() {
+    struct Node {
+        value: usize,
+        prev: *const Self,
+        next: *const Self,
+        log: Rc<RefCell<Vec<usize>>>,
+    }
+    impl Drop for Node {
+        fn drop(&mut self) {
+            self.log.borrow_mut().push(self.value);
+        }
+    }
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let rodeo = Rodeo::new();
+    let slots: Vec<_> = (0..3)
+        .map(|_| rodeo.alloc_node_deferred::<Node>())
+        .collect();
+    let ptrs: Vec<_> = slots
+        .iter()
+        .map(|slot| slot.as_ptr().cast_const())
+        .collect();
+
+    // an abandoned slot is never dropped
+    let _ = rodeo.alloc_node_deferred::<Node>();
+
+    let nodes: Vec<&Node> = slots
+        .into_iter()
+        .enumerate()
+        .map(|(i, slot)| {
+            &*slot.init(Node {
+                value: i,
+                prev: if i == 0 { ptr::null() } else { ptrs[i - 1] },
+                next: ptrs.get(i + 1).copied().unwrap_or(ptr::null()),
+                log: log.clone(),
+            })
+        })
+        .collect();
+
+    let mut forward = Vec::new();
+    let mut current: *const Node = nodes[0];
+    while !current.is_null() {
+        let node = unsafe { &*current };
+        forward.push(node.value);
+        current = node.next;
+    }
+    assert_eq!(forward, [0, 1, 2]);
+    assert_eq!(unsafe { (*nodes[2].prev).value }, 1);
+
+    drop(rodeo);
+    assert_eq!(*log.borrow(), [2, 1, 0]);
+}
+
+#[test]
+fn test_alloc_across_await() {
+    use core::future::Future;
+    use core::pin::pin;
+    use core::task::{Context, Poll, RawWaker, RawWakerVTable, Waker};
+
+    /// Future pending once before completing, like a simulated I/O.
+    struct YieldOnce(bool);
+    impl Future for YieldOnce {
+        type Output = ();
+        fn poll(mut self: core::pin::Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<()> {
+            if self.0 {
+                Poll::Ready(())
+            } else {
+                self.0 = true;
+                cx.waker().wake_by_ref();
+                Poll::Pending
+            }
+        }
+    }
+
+    const VTABLE: RawWakerVTable = RawWakerVTable::new(
+        |_| RawWaker::new(ptr::null(), &VTABLE),
+        |_| {},
+        |_| {},
+        |_| {},
+    );
+
+    let rodeo = Rodeo::new();
+    let future = async {
+        let greeting = rodeo.alloc(String::from("hello"));
+        YieldOnce(false).await;
+        greeting.push_str(", world");
+        let count = rodeo.alloc(1);
+        YieldOnce(false).await;
+        *count += 1;
+        (greeting.as_str(), *count)
+    };
+
+    let waker = unsafe { Waker::from_raw(RawWaker::new(ptr::null(), &VTABLE)) };
+    let mut cx = Context::from_waker(&waker);
+    let mut future = pin!(future);
+    let mut polls = 1;
+    let output = loop {
+        if let Poll::Ready(output) = future.as_mut().poll(&mut cx) {
+            break output;
+        }
+        polls += 1;
+    };
+    assert_eq!(output, ("hello, world", 2));
+    assert_eq!(polls, 3);
+}
+
+#[cfg(feature = "bumpalo")]
+#[test]
+fn test_utilization() {
+    let rodeo = Rodeo::with_allocator(::bumpalo::Bump::with_capacity(4096));
+    let capacity = rodeo.allocator().allocated_bytes();
+    assert!(capacity >= 4096);
+    assert!(rodeo.utilization() < f64::EPSILON);
+
+    let _ = rodeo.alloc_slice_copy(&[0_u8; 1024]);
+    let expected = 1024.0 / f64::from(u32::try_from(capacity).unwrap());
+    assert!((rodeo.utilization() - expected).abs() < f64::EPSILON);
+
+    let _ = rodeo.alloc_slice_copy(&vec![0_u8; capacity - 1024]);
+    assert!(rodeo.utilization() > 0.99);
+}
+
+#[test]
+fn test_header_layout() {
+    let two_words = Layout::new::<(usize, usize)>();
+    if cfg!(debug_assertions) {
+        // plus the finalizer data layout, for debugging purposes
+        let layouts = Layout::new::<(Layout, Layout)>();
+        assert_eq!(HEADER_LAYOUT, two_words.extend(layouts).unwrap().0);
+    } else {
+        let data_layout = Layout::new::<Layout>();
+        assert_eq!(HEADER_LAYOUT, two_words.extend(data_layout).unwrap().0);
+    }
+}
+
+/// Allocator failing once a number of allocations succeeded.
+struct FailAfterAlloc {
+    inner: Alloc,
+    remaining: Cell<usize>,
+}
+
+impl FailAfterAlloc {
+    fn new(successes: usize) -> Self {
+        Self {
+            inner: Alloc::default(),
+            remaining: Cell::new(successes),
+        }
+    }
+}
+
+impl ArenaAlloc for FailAfterAlloc {
+    type Error = Option<<Alloc as ArenaAlloc>::Error>;
+
+    fn try_alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, Self::Error> {
+        let remaining = self.remaining.get().checked_sub(1).ok_or(None)?;
+        self.remaining.set(remaining);
+        self.inner.try_alloc_layout(layout).map_err(Some)
+    }
+}
+
+#[test]
+fn test_alloc_slice_clone_alloc_failure() {
+    #[derive(Debug)]
+    struct Counted(Rc<Cell<usize>>, Rc<Cell<usize>>);
+    impl Clone for Counted {
+        fn clone(&self) -> Self {
+            self.0.set(self.0.get() + 1);
+            Self(self.0.clone(), self.1.clone())
+        }
+    }
+    impl Drop for Counted {
+        fn drop(&mut self) {
+            self.1.set(self.1.get() + 1);
+        }
+    }
+
+    let clones = Rc::new(Cell::new(0));
+    let drops = Rc::new(Cell::new(0));
+    let source = [
+        Counted(clones.clone(), drops.clone()),
+        Counted(clones.clone(), drops.clone()),
+    ];
+
+    let rodeo = Rodeo::with_allocator(FailAfterAlloc::new(1));
+    let _ = rodeo.alloc_slice_clone(&source);
+    assert_eq!((clones.get(), rodeo.finalizers.get()), (2, 1));
+
+    let error = rodeo.try_alloc_slice_clone(&source).unwrap_err();
+    assert!(matches!(error, RodeoError::Alloc(None)));
+    assert_eq!(clones.get(), 2, "nothing cloned");
+    assert_eq!(rodeo.finalizers.get(), 1, "no header linked");
+
+    drop(rodeo);
+    assert_eq!(drops.get(), 2, "only the first clones are dropped");
+    drop(source);
+    assert_eq!(drops.get(), 4);
+}
+
+/// Without bumpalo, the default arena must free its memory (checked by Miri).
+#[cfg(not(feature = "bumpalo"))]
+#[test]
+fn test_default_alloc_frees() {
+    let rodeo: Rodeo<fallback::ChunkedAlloc> = Rodeo::new();
+    for i in 0..100 {
+        let _ = rodeo.alloc(vec![i; 10]);
+        let _ = rodeo.alloc_slice_copy(&[i; 20]);
+    }
+    assert!(rodeo.allocator().chunk_sizes().len() > 1);
+}
+
+/// Allocator reporting when it is dropped.
+struct DropTracking(Alloc, Rc<Cell<bool>>);
+
+impl ArenaAlloc for DropTracking {
+    type Error = <Alloc as ArenaAlloc>::Error;
+
+    fn try_alloc_layout(&self, layout: Layout) -> Result<NonNull<u8>, Self::Error> {
+        self.0.try_alloc_layout(layout)
+    }
+}
+
+impl Drop for DropTracking {
+    fn drop(&mut self) {
+        self.1.set(true);
+    }
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // leaks the value on purpose
+fn test_leak_all() {
+    let value_dropped = Rc::new(Cell::new(false));
+    let alloc_dropped = Rc::new(Cell::new(false));
+    let rodeo = Rodeo::with_allocator(DropTracking(Alloc::default(), alloc_dropped.clone()));
+    let value_dropped1 = value_dropped.clone();
+    let value = rodeo.alloc(DropCallback(move || value_dropped1.set(true)));
+
+    rodeo.leak_all();
+    assert_eq!(rodeo.finalizers.get(), 0);
+    // still valid
+    (value.0)();
+    assert!(value_dropped.get());
+    value_dropped.set(false);
+    let _ = rodeo.alloc(String::from("still usable"));
+    drop(rodeo);
+
+    assert!(!value_dropped.get(), "values are not dropped");
+    assert!(alloc_dropped.get(), "memory is freed");
+}
+
+#[test]
+#[cfg_attr(miri, ignore)] // leaks on purpose
+fn test_forget_all() {
+    let value_dropped = Rc::new(Cell::new(false));
+    let alloc_dropped = Rc::new(Cell::new(false));
+    let rodeo = Rodeo::with_allocator(DropTracking(Alloc::default(), alloc_dropped.clone()));
+    let value_dropped1 = value_dropped.clone();
+    let _ = rodeo.alloc(DropCallback(move || value_dropped1.set(true)));
+
+    rodeo.forget_all();
+
+    assert!(!value_dropped.get(), "values are not dropped");
+    assert!(!alloc_dropped.get(), "memory is not freed");
+}
+
+#[test]
+fn test_neutralize() {
+    std::thread_local! {
+        static DROPPED: Cell<usize> = const { Cell::new(0) };
+    }
+    struct Witness;
+    impl Drop for Witness {
+        fn drop(&mut self) {
+            DROPPED.with(|dropped| dropped.set(dropped.get() + 1));
+        }
+    }
+
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc(Witness);
+    let slice = rodeo.alloc_slice_fill_with(3, |_| Witness);
+    let _ = rodeo.alloc(Witness);
+    rodeo.neutralize_slice_finalizer(slice.as_mut_ptr(), slice.len());
+    assert_eq!(rodeo.finalizers.get(), 2);
+    #[cfg(debug_assertions)]
+    rodeo.debug_check_invariants();
+
+    drop(rodeo);
+    assert_eq!(DROPPED.with(Cell::get), 2);
+}
+
+#[test]
+fn test_vec() {
+    let count = Rc::new(Cell::new(0));
+    let new_item = |i| {
+        let count = count.clone();
+        (i, DropCallback(move || count.set(count.get() + 1)))
+    };
+
+    let rodeo = Rodeo::new();
+    {
+        let mut vec: crate::Vec<'_, _, Alloc> = rodeo.slice_builder();
+        vec.push(new_item(0));
+        vec.extend((1..10).map(new_item));
+        vec.as_mut_slice().reverse();
+        let slice = vec.into_slice();
+        assert!(slice.iter().rev().enumerate().all(|(i, (j, _))| i == *j));
+        assert_eq!(count.get(), 0);
+    }
+    drop(rodeo);
+    assert_eq!(count.get(), 10, "dropped once, by the arena");
+}
+
+#[test]
+fn test_slice_builder() {
+    let count = Rc::new(Cell::new(0));
+    let new_item = || {
+        let count = count.clone();
+        DropCallback(move || count.set(count.get() + 1))
+    };
+
+    let rodeo = Rodeo::new();
+    let mut builder = rodeo.slice_builder();
+    assert!(builder.is_empty());
+    for i in 0..10 {
+        builder.push((i, new_item()));
+        // interleaved allocations do not matter
+        let _ = rodeo.alloc(new_item());
+    }
+    assert_eq!(builder.len(), 10);
+    assert_eq!(count.get(), 0, "growing moves without dropping");
+
+    let slice = builder.finish();
+    assert!(slice.iter().enumerate().all(|(i, (j, _))| i == *j));
+    assert_eq!(rodeo.finalizers.get(), 11);
+    #[cfg(debug_assertions)]
+    rodeo.debug_check_invariants();
+
+    let mut builder = rodeo.slice_builder();
+    builder.push(new_item());
+    builder.push(new_item());
+    drop(builder);
+    assert_eq!(count.get(), 2, "dropping the builder drops its elements");
+
+    assert!(rodeo.slice_builder::<String>().finish().is_empty());
+    let numbers = {
+        let mut builder = rodeo.slice_builder();
+        builder.push(1);
+        builder.extend([2, 3]);
+        builder.as_mut_slice()[2] = 4;
+        builder.finish()
+    };
+    assert_eq!(numbers, &[1, 2, 4]);
+
+    drop(rodeo);
+    assert_eq!(count.get(), 2 + 10 + 10);
+}
+
+#[test]
+fn test_cloneable_rodeo() {
+    use crate::cloneable::CloneableRodeo;
+
+    let rodeo = CloneableRodeo::<Alloc>::default();
+    assert!(rodeo.is_empty());
+    let _ = rodeo.alloc(String::from("one"));
+    let _ = rodeo.alloc(2_u32);
+    let _ = rodeo.alloc(vec![String::from("three")]);
+
+    let mut clone = rodeo.deep_clone();
+    assert_eq!(clone.len(), 3);
+    clone.get_mut::<String>(0).unwrap().push_str(" (cloned)");
+    *clone.get_mut::<u32>(1).unwrap() += 1;
+    clone.get_mut::<Vec<String>>(2).unwrap().clear();
+
+    assert_eq!(rodeo.get::<String>(0).unwrap(), "one");
+    assert_eq!(rodeo.get::<u32>(1), Some(&2));
+    assert_eq!(rodeo.get::<Vec<String>>(2).unwrap(), &["three"]);
+    assert_eq!(clone.get::<String>(0).unwrap(), "one (cloned)");
+    assert_eq!(clone.get::<u32>(1), Some(&3));
+    assert!(clone.get::<Vec<String>>(2).unwrap().is_empty());
+
+    assert_eq!(rodeo.get::<u32>(0), None, "wrong type");
+    assert_eq!(rodeo.get::<u32>(3), None, "out of bounds");
+
+    drop(rodeo);
+    assert_eq!(clone.get::<String>(0).unwrap(), "one (cloned)");
+}
+
+#[test]
+fn test_handle_rodeo() {
+    use crate::handle::{Handle, HandleRodeo};
+
+    struct Node {
+        value: u32,
+        next: Option<Handle<Self>>,
+    }
+
+    let mut rodeo = HandleRodeo::<Alloc>::default();
+    assert!(rodeo.is_empty());
+    let a = rodeo.alloc_handle(Node {
+        value: 1,
+        next: None,
+    });
+    let b = rodeo.alloc_handle(Node {
+        value: 2,
+        next: Some(a),
+    });
+    let name = rodeo.alloc_handle(String::from("cycle"));
+    rodeo.get_mut(a).next = Some(b);
+
+    assert_eq!(rodeo.len(), 3);
+    assert_eq!((a.index(), b.index()), (0, 1));
+    assert_eq!(rodeo.get(a).next, Some(b));
+    let next = rodeo.get(b).next.unwrap();
+    assert_eq!(rodeo.get(next).value, 1);
+    rodeo.get_mut(name).push('!');
+    assert_eq!(rodeo.get(name), "cycle!");
+}
+
+#[test]
+#[should_panic = "invalid handle"]
+fn test_handle_rodeo_invalid() {
+    use crate::handle::HandleRodeo;
+
+    let rodeo = HandleRodeo::<Alloc>::default();
+    let other = HandleRodeo::<Alloc>::default();
+    let _ = other.alloc_handle(0_u8);
+    let handle = other.alloc_handle(1_u32);
+    let _ = rodeo.alloc_handle(2_u32);
+    let _ = rodeo.get(handle);
+}
+
+#[test]
+fn test_alloc_slice_copy_packed() {
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc(0xFF_u8);
+    let values = [0x0102_0304_u32, 0x0506_0708, 0x090A_0B0C];
+    let packed = rodeo.alloc_slice_copy_packed(&values);
+    let start = packed.cast::<u8>().as_ptr();
+
+    assert_eq!(start as usize % 2, 1, "packed right after a byte");
+    assert_eq!(packed.len(), 3);
+    for (i, value) in values.iter().enumerate() {
+        let read = unsafe { packed.cast::<u32>().as_ptr().add(i).read_unaligned() };
+        assert_eq!(read, *value);
+    }
+
+    assert_eq!(rodeo.alloc_slice_copy_packed::<u64>(&[]).len(), 0);
+}
+
+#[test]
+fn test_capabilities() {
+    use crate::fallback::{ChunkedAlloc, LeakingAlloc};
+
+    /// Return the `(CAN_RESET, CAN_DEALLOC, CAN_RESERVE)` flags of `A`.
+    const fn capabilities<A: ArenaAlloc>() -> (bool, bool, bool) {
+        (A::CAN_RESET, A::CAN_DEALLOC, A::CAN_RESERVE)
+    }
+
+    #[cfg(feature = "bumpalo")]
+    assert_eq!(capabilities::<::bumpalo::Bump>(), (true, true, false));
+    assert_eq!(capabilities::<LeakingAlloc>(), (false, false, false));
+    assert_eq!(capabilities::<ChunkedAlloc>(), (false, true, false));
+}
+
+#[test]
+fn test_layout_overflow() {
+    let rodeo = Rodeo::new();
+    let extreme_layouts = [
+        // alignment beyond what the header can be padded to
+        Layout::from_size_align(0, 1 << (usize::BITS - 1)).unwrap(),
+        Layout::from_size_align(1 << (usize::BITS - 2), 1 << (usize::BITS - 2)).unwrap(),
+        // size leaving no room for the header
+        Layout::from_size_align(isize::MAX as usize - 7, 8).unwrap(),
+    ];
+    for layout in extreme_layouts {
+        let result = rodeo.try_alloc_layout_with_finalizer_in(None, layout, noop_finalizer, ());
+        assert_eq!(
+            result.unwrap_err(),
+            RodeoError::LayoutOverflow,
+            "{layout:?}"
+        );
+    }
+    assert!(rodeo.last.get().is_none());
+
+    let result = rodeo.try_alloc_slice_with(usize::MAX / 4, |_| String::new());
+    assert_eq!(result.unwrap_err(), RodeoError::LayoutOverflow);
+    let result = rodeo.try_alloc_slice_with(usize::MAX / 4, |_| 0_u64);
+    assert_eq!(result.unwrap_err(), RodeoError::LayoutOverflow);
+    let result = rodeo.try_alloc_slice_fill_with(usize::MAX / 4, |_| String::new());
+    assert_eq!(result.unwrap_err(), RodeoError::LayoutOverflow);
+    let result = rodeo.try_alloc_slice_clone_with_capacity(&[String::new()], usize::MAX / 4);
+    assert_eq!(result.unwrap_err(), RodeoError::LayoutOverflow);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "corrupted finalizer chain")]
+#[cfg_attr(miri, ignore)] // leaks the unreachable values
+fn test_corrupted_head() {
+    let outside = [0_usize; 8];
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc(String::from("first"));
+    let _ = rodeo.alloc(String::from("last"));
+    rodeo.last.set(Some(NonNull::from(&outside).cast()));
+    drop(rodeo);
+}
+
+#[cfg(debug_assertions)]
+#[test]
+fn test_corrupted_previous() {
+    let outside = [0_usize; 8];
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc(String::from("first"));
+    let _ = rodeo.alloc(String::from("last"));
+    let mut last = rodeo.last.get().unwrap();
+    let original = unsafe { last.as_mut() }
+        .previous
+        .replace(NonNull::from(&outside).cast());
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        rodeo.debug_check_invariants();
+    }));
+
+    // restore the header so that the drop is correct
+    unsafe { last.as_mut() }.previous = original;
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_allocator_contains() {
+    let rodeo = Rodeo::new();
+    let inside = NonNull::from(rodeo.alloc(1_u64)).cast();
+    let outside = NonNull::from(&0_u64).cast();
+    assert!(rodeo.allocator().contains(inside));
+    assert!(!rodeo.allocator().contains(outside));
+}
+
+#[test]
+fn test_alloc_soa() {
+    let rodeo = Rodeo::new();
+    let lens = [5, 3, 7];
+    let sizes = [1, 8, 2];
+    let aligns = [1, mem::align_of::<u64>(), 2];
+    let ptrs = rodeo.alloc_soa(lens, sizes, aligns);
+
+    let mut ranges: Vec<_> = (0..3)
+        .map(|i| {
+            let start = ptrs[i].as_ptr() as usize;
+            assert_eq!(start % aligns[i], 0);
+            start..start + lens[i] * sizes[i]
+        })
+        .collect();
+    ranges.sort_by_key(|range| range.start);
+    assert!(ranges.windows(2).all(|pair| pair[0].end <= pair[1].start));
+
+    unsafe {
+        ptrs[1].cast::<u64>().as_ptr().add(2).write(42);
+        assert_eq!(ptrs[1].cast::<u64>().as_ptr().add(2).read(), 42);
+    }
+
+    assert_eq!(
+        rodeo.try_alloc_soa([1], [1], [3]).unwrap_err(),
+        RodeoError::LayoutOverflow
+    );
+    assert_eq!(
+        rodeo.try_alloc_soa([usize::MAX], [2], [1]).unwrap_err(),
+        RodeoError::LayoutOverflow
+    );
+}
+
+#[cfg(feature = "bumpalo")]
+#[test]
+fn test_scratch() {
+    let mut rodeo = Rodeo::with_allocator(::bumpalo::Bump::with_capacity(256));
+    let capacity = rodeo.allocator().chunk_capacity();
+    let input = b"aaabccddddde";
+
+    // run-length compaction into the scratch space, then commit the result
+    let scratch = rodeo.scratch();
+    assert_eq!(scratch.len(), capacity);
+    let mut len = 0;
+    for &b in input {
+        if len > 0 && unsafe { scratch[len - 2].assume_init() } == b {
+            unsafe { *scratch[len - 1].assume_init_mut() += 1 };
+        } else {
+            scratch[len].write(b);
+            scratch[len + 1].write(1);
+            len += 2;
+        }
+    }
+    let compacted = unsafe { rodeo.commit(len) };
+    assert_eq!(compacted, b"a\x03b\x01c\x02d\x05e\x01");
+    assert_eq!(
+        rodeo.allocator().chunk_capacity(),
+        capacity - compacted.len()
+    );
+
+    let other = rodeo.alloc_slice_copy(&[0xff_u8; 8]);
+    assert_eq!(compacted, b"a\x03b\x01c\x02d\x05e\x01");
+    assert_eq!(other, [0xff; 8]);
+
+    let empty = unsafe { rodeo.commit(0) };
+    assert!(empty.is_empty());
+}
+
+#[cfg(feature = "bumpalo")]
+#[test]
+#[should_panic = "committed more than the scratch space"]
+fn test_scratch_overcommit() {
+    let mut rodeo = Rodeo::with_allocator(::bumpalo::Bump::with_capacity(64));
+    let len = rodeo.scratch().len();
+    let _ = unsafe { rodeo.commit(len + 1) };
+}
+
+#[test]
+fn test_alloc_slice_clone_with_capacity() {
+    let drops = Rc::new(Cell::new(0));
+    let counter = {
+        let drops = drops.clone();
+        DropCallback(move || drops.set(drops.get() + 1))
+    };
+    let src = [counter.clone(), counter];
+    {
+        let rodeo = Rodeo::new();
+        let (slice, spare) = rodeo.alloc_slice_clone_with_capacity(&src, 5);
+        assert_eq!(slice.len(), 2);
+        assert_eq!(spare, 3);
+
+        let (empty, spare) = rodeo.alloc_slice_clone_with_capacity(&src[..0], 2);
+        assert!(empty.is_empty());
+        assert_eq!(spare, 2);
+
+        #[cfg(debug_assertions)]
+        rodeo.debug_check_invariants();
+    }
+    // only the two clones, not the spare capacity
+    assert_eq!(drops.get(), 2);
+    drop(src);
+    assert_eq!(drops.get(), 4);
+}
+
+#[test]
+fn test_keyed_rodeo() {
+    use core::cmp::Reverse;
+
+    use crate::keyed::KeyedRodeo;
+
+    let order = Rc::new(RefCell::new(Vec::new()));
+    let witness = |name: &'static str| {
+        let order = order.clone();
+        DropCallback(move || order.borrow_mut().push(name))
+    };
+
+    {
+        let rodeo = KeyedRodeo::<u32, Alloc>::default();
+        let _ = rodeo.rodeo().alloc(witness("unkeyed"));
+        let _ = rodeo.alloc_with_key(3, witness("c"));
+        let _ = rodeo.alloc_with_key(1, witness("a"));
+        let _ = rodeo.alloc_with_key(2, witness("b1"));
+        let _ = rodeo.alloc_with_key(2, witness("b2"));
+        assert_eq!(*rodeo.alloc_with_key(0, 42), 42);
+        let forgotten = rodeo.alloc_with_key(0, witness("forgotten"));
+        rodeo.rodeo().forget(forgotten);
+        assert_eq!(rodeo.rodeo().num_pending_finalizers(), 5);
+        assert_eq!(rodeo.rodeo().pending_layouts().count(), 5);
+    }
+    assert_eq!(*order.borrow(), ["a", "b1", "b2", "c", "unkeyed"]);
+
+    order.borrow_mut().clear();
+    {
+        let rodeo = KeyedRodeo::<Reverse<u32>, Alloc>::default();
+        let _ = rodeo.alloc_with_key(Reverse(1), witness("a"));
+        let _ = rodeo.alloc_with_key(Reverse(3), witness("c"));
+        let _ = rodeo.alloc_with_key(Reverse(2), witness("b"));
+    }
+    assert_eq!(*order.borrow(), ["c", "b", "a"]);
+}
+
+#[test]
+fn test_try_into_allocator() {
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc(42);
+    let _ = rodeo.alloc_slice_copy(&[1, 2, 3]);
+    assert!(rodeo.try_into_allocator().is_ok());
+
+    let noop = DropCallback(|| ());
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc(noop.clone());
+    let _ = rodeo.alloc_slice_clone(&[noop.clone(), noop.clone()]);
+    let drained = rodeo.alloc_slice_clone(&[noop]);
+    drop(unsafe { rodeo.drain_slice_to_vec(drained) });
+    let _ = rodeo.alloc(0_u8);
+    let Err((_, leaked)) = rodeo.try_into_allocator() else {
+        panic!("leak not reported");
+    };
+    assert_eq!(leaked, 2);
+}
+
+#[test]
+fn test_alloc_ref_slice() {
+    let rodeo = Rodeo::new();
+    let refs = rodeo.alloc_ref_slice((0..4).map(|i| vec![i]));
+    assert_eq!(refs.len(), 4);
+    for (i, v) in refs.iter_mut().enumerate() {
+        v.push(i * 10);
+    }
+    assert_eq!(*refs[3], [3, 30]);
+    // header per value, none for the slice of references
+    assert_eq!(rodeo.finalizers.get(), 4);
+
+    let empty = rodeo.alloc_ref_slice(core::iter::empty::<String>());
+    assert!(empty.is_empty());
+}
+
+#[test]
+fn test_alloc_with() {
+    let rodeo = Rodeo::new();
+    let big = rodeo.alloc_with(|| [7_u8; 4096]);
+    assert!(big.iter().all(|&b| b == 7));
+    assert_eq!(rodeo.alloc_with(|| String::from("lazy")), "lazy");
+    assert_eq!(rodeo.finalizers.get(), 1);
+
+    let failing = Rodeo::with_allocator(FailingAlloc);
+    let result = failing.try_alloc_with(|| -> String { panic!("must not be called") });
+    assert!(result.is_err());
+
+    // a panicking initializer leaves no finalizer behind
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        rodeo.alloc_with(|| -> String { panic!("initializer panicked") });
+    }));
+    assert!(result.is_err());
+    assert_eq!(rodeo.finalizers.get(), 1);
+}
+
+#[test]
+fn test_data_rodeo() {
+    use crate::data::DataRodeo;
+    use crate::fallback::LeakingAlloc;
+
+    // no finalizer logic at all, only the allocator is dropped
+    assert!(!mem::needs_drop::<DataRodeo<LeakingAlloc>>());
+
+    let arena = DataRodeo::<Alloc>::default();
+    let value = arena.alloc([1_u64, 2, 3]);
+    value[0] = 10;
+    let slice = arena.alloc_slice_copy(&[1_u16, 2, 3]);
+    let empty = arena.alloc_slice_copy::<u32>(&[]);
+    let s = arena.alloc_str("data");
+    assert_eq!(*value, [10, 2, 3]);
+    assert_eq!(slice, [1, 2, 3]);
+    assert!(empty.is_empty());
+    assert_eq!(s, "data");
+
+    let failing = DataRodeo::with_allocator(FailingAlloc);
+    assert!(failing.try_alloc(0_u8).is_err());
+}
+
+#[test]
+fn test_alloc_default() {
+    let rodeo = Rodeo::new();
+    let n: &mut u32 = rodeo.alloc_default();
+    assert_eq!(*n, 0);
+    let s: &mut String = rodeo.alloc_default();
+    s.push_str("cleaned up");
+    assert_eq!(rodeo.finalizers.get(), 1);
+
+    let failing = Rodeo::with_allocator(FailingAlloc);
+    assert!(failing.try_alloc_default::<Vec<u8>>().is_err());
+}
+
+#[test]
+fn test_slab() {
+    use crate::slab::Slab;
+
+    let drops = Rc::new(Cell::new(0));
+    let counter = {
+        let drops = drops.clone();
+        DropCallback(move || drops.set(drops.get() + 1))
+    };
+
+    let rodeo = Rodeo::new();
+    {
+        let mut slab = Slab::new(&rodeo);
+        let keys: Vec<_> = (0..100)
+            .map(|i| slab.insert((i, counter.clone())))
+            .collect();
+        assert_eq!(slab.len(), 100);
+        assert!(keys
+            .iter()
+            .enumerate()
+            .all(|(i, &k)| slab.get(k).unwrap().0 == i));
+
+        slab.get_mut(keys[70]).unwrap().0 = 700;
+        assert_eq!(slab.get(keys[70]).unwrap().0, 700);
+
+        // removal runs the destructor once the value is dropped
+        let removed = slab.remove(keys[3]).unwrap();
+        assert_eq!(removed.0, 3);
+        drop(removed);
+        assert_eq!(drops.get(), 1);
+        assert!(slab.remove(keys[3]).is_none());
+        assert!(!slab.contains(keys[3]));
+
+        // the slot is reused with a new generation
+        let reused = slab.insert((1000, counter));
+        assert_eq!(reused.index(), keys[3].index());
+        assert_ne!(reused.generation(), keys[3].generation());
+        assert!(slab.get(keys[3]).is_none());
+        assert_eq!(slab.get(reused).unwrap().0, 1000);
+        assert_eq!(slab.len(), 100);
+    }
+    // the remaining values are dropped with the slab, not the arena
+    assert_eq!(drops.get(), 101);
+    assert_eq!(rodeo.finalizers.get(), 0);
+}
+
+#[test]
+fn test_alloc_slice_concat() {
+    let rodeo = Rodeo::new();
+
+    let joined = rodeo.alloc_slice_concat(&[&[1u32, 2][..], &[], &[3, 4, 5]]);
+    assert_eq!(joined, &[1, 2, 3, 4, 5]);
+
+    let empty = rodeo.alloc_slice_concat::<u32>(&[]);
+    assert!(empty.is_empty());
+    let empty = rodeo.alloc_slice_concat::<u32>(&[&[], &[]]);
+    assert!(empty.is_empty());
+
+    let units = rodeo.alloc_slice_concat(&[&[(); 3][..], &[(); 2]]);
+    assert_eq!(units.len(), 5);
+
+    let huge =
+        unsafe { core::slice::from_raw_parts(NonNull::<()>::dangling().as_ptr(), usize::MAX) };
+    assert!(matches!(
+        rodeo.try_alloc_slice_concat(&[huge, &[()]]),
+        Err(RodeoError::LayoutOverflow)
+    ));
+}
+
+#[test]
+fn test_alloc_slice_fill_copy() {
+    let rodeo = Rodeo::with_allocator(FailAfterAlloc::new(1));
+    assert_eq!(rodeo.alloc_slice_fill_copy(3, 7_u32), [7, 7, 7]);
+    // no allocation for empty slices and zero-sized types
+    assert!(rodeo.alloc_slice_fill_copy(0, 7_u32).is_empty());
+    assert_eq!(rodeo.alloc_slice_fill_copy(1000, ()).len(), 1000);
+    assert!(rodeo.try_alloc_slice_fill_copy(1, 7_u32).is_err());
+    assert_eq!(
+        rodeo
+            .try_alloc_slice_fill_copy(usize::MAX, 0_u32)
+            .unwrap_err(),
+        RodeoError::LayoutOverflow
+    );
+}
+
+#[test]
+fn test_alloc_slice_fill_clone() {
+    struct FailingClone {
+        clones: Rc<Cell<usize>>,
+        drops: Rc<Cell<usize>>,
+    }
+    impl Clone for FailingClone {
+        fn clone(&self) -> Self {
+            assert!(self.clones.get() < 2, "clone failed");
+            self.clones.set(self.clones.get() + 1);
+            Self {
+                clones: self.clones.clone(),
+                drops: self.drops.clone(),
+            }
+        }
+    }
+    impl Drop for FailingClone {
+        fn drop(&mut self) {
+            self.drops.set(self.drops.get() + 1);
+        }
+    }
+
+    let rodeo = Rodeo::new();
+    let words = rodeo.alloc_slice_fill_clone(3, &String::from("word"));
+    assert_eq!(words, ["word", "word", "word"]);
+    assert_eq!(rodeo.finalizers.get(), 1);
+    assert!(rodeo
+        .alloc_slice_fill_clone(3, &0_u8)
+        .iter()
+        .all(|&b| b == 0));
+    assert_eq!(rodeo.finalizers.get(), 1);
+
+    let prototype = FailingClone {
+        clones: Rc::new(Cell::new(0)),
+        drops: Rc::new(Cell::new(0)),
+    };
+    let drops = prototype.drops.clone();
+    {
+        let rodeo = Rodeo::new();
+        let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+            let _ = rodeo.alloc_slice_fill_clone(5, &prototype);
+        }));
+        assert!(result.is_err());
+        assert_eq!(drops.get(), 2);
+    }
+    // the two clones are not dropped again by the arena
+    assert_eq!(drops.get(), 2);
+}
+
+#[cfg(feature = "arrayvec")]
+#[test]
+fn test_alloc_from_arrayvec() {
+    use ::arrayvec::ArrayVec;
+
+    let drops = Rc::new(Cell::new(0));
+    let counter = {
+        let drops = drops.clone();
+        DropCallback(move || drops.set(drops.get() + 1))
+    };
+
+    let mut array = ArrayVec::<_, 4>::new();
+    array.push((String::from("a"), counter.clone()));
+    array.push((String::from("b"), counter));
+    {
+        let rodeo = Rodeo::new();
+        let slice = rodeo.alloc_from_arrayvec(array);
+        assert_eq!(slice.len(), 2);
+        assert_eq!(slice[1].0, "b");
+        assert_eq!(drops.get(), 0);
+    }
+    assert_eq!(drops.get(), 2);
+
+    let mut array = ArrayVec::<_, 4>::new();
+    array.push({
+        let drops = drops.clone();
+        DropCallback(move || drops.set(drops.get() + 1))
+    });
+    let failing = Rodeo::with_allocator(FailingAlloc);
+    assert!(failing.try_alloc_from_arrayvec(array).is_err());
+    assert_eq!(drops.get(), 3, "dropped along with the array");
+}
+
+#[test]
+fn test_alloc_slice_fill_with() {
+    let drops = Rc::new(Cell::new(0));
+    let rodeo = Rodeo::new();
+    let words = rodeo.alloc_slice_fill_with(3, |i| i.to_string());
+    assert_eq!(words, ["0", "1", "2"]);
+    assert_eq!(rodeo.finalizers.get(), 1);
+
+    let result = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _ = rodeo.alloc_slice_fill_with(5, |i| {
+            assert!(i < 3, "initializer panicked");
+            let drops = drops.clone();
+            DropCallback(move || drops.set(drops.get() + 1))
+        });
+    }));
+    assert!(result.is_err());
+    // the three initialized elements are dropped, and no finalizer remains
+    assert_eq!(drops.get(), 3);
+    assert_eq!(rodeo.finalizers.get(), 1);
+    drop(rodeo);
+    assert_eq!(drops.get(), 3);
+}
+
+#[cfg(feature = "bumpalo")]
+#[test]
+fn test_undo_last() {
+    let drops = Rc::new(Cell::new(0));
+    let counter = {
+        let drops = drops.clone();
+        DropCallback(move || drops.set(drops.get() + 1))
+    };
+
+    let mut rodeo = Rodeo::with_allocator(::bumpalo::Bump::with_capacity(1024));
+    let _ = rodeo.alloc(42_u64);
+    let capacity = rodeo.allocator().chunk_capacity();
+    let _ = rodeo.alloc(counter.clone());
+    assert!(rodeo.allocator().chunk_capacity() < capacity);
+
+    assert!(rodeo.undo_last());
+    assert_eq!(drops.get(), 1);
+    assert_eq!(rodeo.finalizers.get(), 0);
+    assert_eq!(rodeo.allocator().chunk_capacity(), capacity);
+    #[cfg(debug_assertions)]
+    rodeo.debug_check_invariants();
+
+    // a single step only
+    assert!(!rodeo.undo_last());
+
+    // not the last allocation anymore
+    let _ = rodeo.alloc(counter);
+    let _ = rodeo.allocator().alloc(0_u8);
+    assert!(!rodeo.undo_last());
+    assert_eq!(drops.get(), 1);
+    drop(rodeo);
+    assert_eq!(drops.get(), 2);
+}
+
+#[test]
+fn test_num_allocations() {
+    let mut rodeo = Rodeo::new();
+    assert_eq!(rodeo.num_allocations(), 0);
+    let _ = rodeo.alloc(42_u64);
+    let _ = rodeo.alloc_str("not counted");
+    assert_eq!(rodeo.num_allocations(), 0);
+    let _ = rodeo.alloc(String::from("a"));
+    let _ = rodeo.alloc_slice_fill_clone(3, &String::from("b"));
+    assert_eq!(rodeo.num_allocations(), 2);
+
+    let _ = rodeo.alloc(String::from("c"));
+    assert!(rodeo.undo_last());
+    assert_eq!(rodeo.num_allocations(), 2);
+    rodeo.drop_all();
+    assert_eq!(rodeo.num_allocations(), 0);
+}
+
+#[cfg(feature = "bumpalo")]
+#[test]
+fn test_debug() {
+    let rodeo = Rodeo::<::bumpalo::Bump>::default();
+    let _ = rodeo.alloc(42_u64);
+    let _ = rodeo.alloc(String::from("a"));
+    let _ = rodeo.alloc_slice_fill_clone(3, &String::from("b"));
+
+    let debug = alloc::format!("{rodeo:?}");
+    assert!(debug.starts_with("Rodeo { allocator: Bump"));
+    assert!(debug.contains("finalizers: 2"));
+    let slice = Layout::array::<String>(3).unwrap();
+    let single = Layout::new::<String>();
+    assert!(debug.contains(&alloc::format!("pending: [{slice:?}, {single:?}]")));
+}
+
+#[test]
+fn test_num_pending_finalizers() {
+    let mut rodeo = Rodeo::new();
+    assert_eq!(rodeo.num_pending_finalizers(), 0);
+    let _ = rodeo.alloc(1_u32);
+    let _ = rodeo.alloc_str("not droppable");
+    assert_eq!(rodeo.num_pending_finalizers(), 0);
+
+    let forgotten = rodeo.alloc(String::from("forgotten"));
+    let _ = rodeo.alloc_slice_clone(&[vec![1], vec![2]]);
+    let _ = rodeo.alloc(Box::new(3));
+    assert_eq!(rodeo.num_pending_finalizers(), 3);
+    assert_eq!(
+        rodeo.num_pending_finalizers(),
+        rodeo.pending_layouts().count()
+    );
+
+    let mut leaked = String::new();
+    mem::swap(forgotten, &mut leaked);
+    rodeo.forget(forgotten);
+    assert_eq!(rodeo.num_pending_finalizers(), 2);
+
+    assert!(rodeo.undo_last());
+    assert_eq!(rodeo.num_pending_finalizers(), 1);
+    rodeo.drop_all();
+    assert_eq!(rodeo.num_pending_finalizers(), 0);
+}
+
+#[test]
+fn test_unlink() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let logger = |name: &'static str| {
+        let log = log.clone();
+        DropCallback(move || log.borrow_mut().push(name))
+    };
+
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc(logger("first"));
+    let _ = rodeo.alloc(logger("middle"));
+    let _ = rodeo.alloc(logger("last"));
+
+    let last = rodeo.last.get().unwrap();
+    let middle = unsafe { last.as_ref() }.previous.unwrap();
+    rodeo.unlink(Some(last), middle);
+    assert_eq!(rodeo.num_pending_finalizers(), 2);
+    rodeo.unlink(None, last);
+    assert_eq!(rodeo.num_pending_finalizers(), 1);
+    #[cfg(debug_assertions)]
+    rodeo.debug_check_invariants();
+
+    drop(rodeo);
+    assert_eq!(*log.borrow(), ["first"]);
+}
+
+#[test]
+fn test_generation() {
+    let mut rodeo = Rodeo::new();
+    assert_eq!(rodeo.generation(), 0);
+    let _ = rodeo.alloc(String::from("first"));
+    rodeo.reset();
+    assert_eq!(rodeo.generation(), 1);
+    let _ = rodeo.alloc(String::from("second"));
+    rodeo.reset();
+    assert_eq!(rodeo.generation(), 2);
+
+    // dropping values early is not a reset
+    let _ = rodeo.alloc(String::from("third"));
+    rodeo.drop_all();
+    assert_eq!(rodeo.generation(), 2);
+}
+
+#[test]
+fn test_pending_layouts() {
+    let rodeo = Rodeo::new();
+    assert_eq!(rodeo.last_layout(), None);
+    assert_eq!(rodeo.pending_layouts().count(), 0);
+
+    let _ = rodeo.alloc(String::new());
+    let _ = rodeo.alloc(42_u64);
+    let mut builder = rodeo.slice_builder();
+    builder.extend([vec![1_u8], vec![2], vec![3]]);
+    let _ = builder.finish();
+    let _ = rodeo.alloc_slice_clone_with_capacity(&[Box::new(1_u8)], 4);
+
+    let boxes = Layout::new::<[Box<u8>; 1]>();
+    assert_eq!(
+        rodeo.last_layout(),
+        Some(boxes),
+        "only the initialized elements"
+    );
+    let layouts: Vec<_> = rodeo.pending_layouts().collect();
+    assert_eq!(
+        layouts,
+        [
+            boxes,
+            Layout::new::<[Vec<u8>; 3]>(),
+            Layout::new::<String>()
+        ]
+    );
+}
+
+#[test]
+fn test_defer() {
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let rodeo = Rodeo::new();
+    let log1 = log.clone();
+    let _ = rodeo.alloc(DropCallback(move || log1.borrow_mut().push("value")));
+    let log1 = log.clone();
+    rodeo.defer(move || log1.borrow_mut().push("deferred"));
+    assert_eq!(rodeo.num_allocations(), 2);
+    assert!(log.borrow().is_empty());
+    drop(rodeo);
+    assert_eq!(*log.borrow(), ["deferred", "value"]);
+
+    let called = Rc::new(Cell::new(false));
+    let rodeo = Rodeo::with_allocator(FailingAlloc);
+    let called1 = called.clone();
+    assert!(rodeo.try_defer(move || called1.set(true)).is_err());
+    assert!(!called.get());
+    assert_eq!(Rc::strong_count(&called), 1);
+}
+
+#[test]
+fn test_drop_order_fifo() {
+    /// Value reading another value of the arena when dropped.
+    struct Reader(*const String, Rc<RefCell<Vec<String>>>);
+
+    impl Drop for Reader {
+        fn drop(&mut self) {
+            let read = unsafe { &*self.0 }.clone();
+            self.1.borrow_mut().push(read);
+        }
+    }
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let rodeo = Rodeo::with_allocator_ordered(Alloc::default(), DropOrder::Fifo);
+    let reader = rodeo.alloc(Reader(ptr::null(), log.clone()));
+    // allocated after the reader, dropped after it all the same
+    let read = rodeo.alloc(String::from("still alive"));
+    reader.0 = read;
+    let log1 = log.clone();
+    rodeo.defer(move || log1.borrow_mut().push(String::from("last")));
+    drop(rodeo);
+    assert_eq!(*log.borrow(), ["still alive", "last"]);
+
+    let log = Rc::new(RefCell::new(Vec::new()));
+    let mut rodeo = Rodeo::with_allocator_ordered(Alloc::default(), DropOrder::Fifo);
+    for i in 0..3 {
+        let log = log.clone();
+        rodeo.defer(move || log.borrow_mut().push(i.to_string()));
+    }
+    rodeo.drop_all();
+    assert_eq!(*log.borrow(), ["0", "1", "2"]);
+    assert!(rodeo.is_empty());
+}
+
+#[test]
+fn test_forget() {
+    let drops = Rc::new(RefCell::new(Vec::new()));
+    let rodeo = Rodeo::new();
+    let callback = |i| {
+        let drops = drops.clone();
+        DropCallback(move || drops.borrow_mut().push(i))
+    };
+    let _ = rodeo.alloc(callback(0));
+    let middle = rodeo.alloc(callback(1));
+    let _ = rodeo.alloc(callback(2));
+    rodeo.forget(middle);
+    assert_eq!(rodeo.num_allocations(), 2);
+    #[cfg(debug_assertions)]
+    rodeo.debug_check_invariants();
+
+    let copy = rodeo.alloc(42_u64);
+    rodeo.forget(copy);
+    drop(rodeo);
+    assert_eq!(*drops.borrow(), [2, 0]);
+}
+
+#[test]
+#[should_panic = "value not found among the pending finalizers"]
+fn test_forget_unknown() {
+    let rodeo = Rodeo::new();
+    let _ = rodeo.alloc(String::from("allocated"));
+    rodeo.forget(&mut String::from("not allocated"));
+}
+
+#[test]
+fn test_alloc_shared() {
+    let rodeo = Rodeo::new();
+    let value = rodeo.alloc_shared(String::from("value"));
+    let slice = rodeo.alloc_slice_copy_shared(&[1, 2, 3]);
+    let string = rodeo.alloc_str_shared("string");
+    let aliases = [(value, slice, string), (value, slice, string)];
+    assert_eq!(aliases[0], aliases[1]);
+    assert_eq!(value, "value");
+    assert_eq!(slice, [1, 2, 3]);
+    assert_eq!(string, "string");
+    assert_eq!(rodeo.num_allocations(), 1);
+}
+
+#[test]
+fn test_try_alloc_layout() {
+    let rodeo = Rodeo::new();
+    let layout = Layout::from_size_align(24, 8).unwrap();
+    let ptr = rodeo.try_alloc_layout(layout).unwrap();
+    assert_eq!(ptr.as_ptr() as usize % 8, 0);
+    unsafe { ptr.as_ptr().write_bytes(0xAA, 24) };
+    assert_eq!(rodeo.allocated_bytes(), 24);
+    assert!(rodeo.is_empty());
+
+    let rodeo = Rodeo::with_allocator(FailingAlloc);
+    assert!(matches!(
+        rodeo.try_alloc_layout(layout),
+        Err(RodeoError::Alloc(_))
+    ));
+}
+
+#[test]
+fn test_try_alloc_with_finalizer() {
+    std::thread_local! {
+        static FINALIZED: RefCell<Vec<u64>> = const { RefCell::new(Vec::new()) };
+    }
+
+    unsafe fn record(ptr: NonNull<u8>) {
+        let value = unsafe { ptr.cast::<u64>().as_ptr().read() };
+        FINALIZED.with(|finalized| finalized.borrow_mut().push(value));
+    }
+
+    let mut rodeo = Rodeo::new();
+    for value in [1_u64, 2] {
+        let layout = Layout::from_size_align(8, 64).unwrap();
+        let ptr = unsafe { rodeo.try_alloc_with_finalizer(layout, record) }.unwrap();
+        assert_eq!(ptr.as_ptr() as usize % 64, 0);
+        unsafe { ptr.cast::<u64>().as_ptr().write(value) };
+    }
+    assert_eq!(rodeo.num_allocations(), 2);
+    #[cfg(debug_assertions)]
+    rodeo.debug_check_invariants();
+
+    rodeo.drop_all();
+    FINALIZED.with(|finalized| assert_eq!(*finalized.borrow(), [2, 1]));
+}
+
+#[cfg(feature = "allocator_api")]
+#[test]
+fn test_allocator_api() {
+    let drops = Rc::new(Cell::new(0));
+    let counter = {
+        let drops = drops.clone();
+        DropCallback(move || drops.set(drops.get() + 1))
+    };
+
+    let rodeo = Rodeo::new();
+    let mut v = Vec::new_in(&rodeo);
+    for _ in 0..100 {
+        v.push(counter.clone());
+    }
+    assert!(rodeo.allocated_bytes() >= 100 * mem::size_of_val(&counter));
+    v.truncate(10);
+    v.shrink_to_fit();
+    assert_eq!(drops.get(), 90);
+    drop(v);
+    assert_eq!(drops.get(), 100);
+    assert!(rodeo.is_empty());
+}
+
+#[cfg(feature = "hashbrown")]
+#[test]
+fn test_alloc_hashmap() {
+    let drops = Rc::new(Cell::new(0));
+    let counter = {
+        let drops = drops.clone();
+        DropCallback(move || drops.set(drops.get() + 1))
+    };
+
+    let rodeo = Rodeo::new();
+    let mut map = rodeo.alloc_hashmap();
+    for i in 0..100_u32 {
+        map.insert(i, counter.clone());
+    }
+    assert_eq!(map.len(), 100);
+    assert!(map.contains_key(&42));
+    assert!(rodeo.allocated_bytes() >= 100 * mem::size_of_val(&counter));
+    map.retain(|&i, _| i < 10);
+    assert_eq!(drops.get(), 90);
+    drop(map);
+    assert_eq!(drops.get(), 100);
+    assert!(rodeo.is_empty(), "entries dropped by the map itself");
+}
+
+#[cfg(all(feature = "std", feature = "bumpalo"))]
+#[test]
+fn test_sync_rodeo() {
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Arc;
+
+    use crate::sync::SyncRodeo;
+
+    struct Counter(Arc<AtomicUsize>);
+
+    impl Drop for Counter {
+        fn drop(&mut self) {
+            self.0.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    let drops = Arc::new(AtomicUsize::new(0));
+    let rodeo = SyncRodeo::<::bumpalo::Bump>::default();
+    std::thread::scope(|s| {
+        for i in 0..4_u8 {
+            let rodeo = &rodeo;
+            let drops = drops.clone();
+            s.spawn(move || {
+                for _ in 0..100 {
+                    let _ = rodeo.alloc(Counter(drops.clone()));
+                }
+                assert_eq!(rodeo.alloc_str("str"), "str");
+                assert_eq!(rodeo.alloc_slice_copy(&[i; 3]), [i; 3]);
+                assert_eq!(rodeo.alloc_slice_clone(&[String::new()]).len(), 1);
+            });
+        }
+    });
+
+    let rodeo = rodeo.into_inner();
+    assert_eq!(rodeo.num_allocations(), 404);
+    drop(rodeo);
+    assert_eq!(drops.load(Ordering::Relaxed), 400);
+}
+
+#[cfg(all(feature = "std", feature = "bumpalo"))]
+#[test]
+#[cfg_attr(miri, ignore)] // leaks the arena
+fn test_sync_rodeo_reentrant_clone() {
+    use crate::sync::SyncRodeo;
+
+    struct Reentrant(&'static SyncRodeo<::bumpalo::Bump>);
+
+    impl Clone for Reentrant {
+        fn clone(&self) -> Self {
+            let _ = self.0.alloc_str("allocated while cloning");
+            Self(self.0)
+        }
+    }
+
+    let rodeo = Box::leak(Box::new(SyncRodeo::<::bumpalo::Bump>::default()));
+    let clones = rodeo.alloc_slice_clone(&[Reentrant(rodeo), Reentrant(rodeo)]);
+    assert_eq!(clones.len(), 2);
+}
+
+#[test]
+fn test_is_empty() {
+    let mut rodeo = Rodeo::new();
+    assert!(rodeo.is_empty());
+    let _ = rodeo.alloc(42_u64);
+    assert!(rodeo.is_empty());
+    let _ = rodeo.alloc(String::from("a"));
+    assert!(!rodeo.is_empty());
+    rodeo.drop_all();
+    assert!(rodeo.is_empty());
+    let _ = rodeo.alloc(String::from("b"));
+    assert!(!rodeo.is_empty());
+    rodeo.reset();
+    assert!(rodeo.is_empty());
+}
+
+#[test]
+fn test_allocated_bytes() {
+    let mut rodeo = Rodeo::new();
+    assert_eq!(rodeo.allocated_bytes(), 0);
+    let _ = rodeo.alloc(42_u64);
+    let _ = rodeo.alloc_str("abc");
+    assert_eq!(rodeo.allocated_bytes(), 11);
+    let _ = rodeo.alloc(String::new());
+    let with_header = rodeo.allocated_bytes() - 11;
+    assert!(with_header >= HEADER_LAYOUT.size() + mem::size_of::<String>());
+    assert!(rodeo.undo_last());
+    assert_eq!(rodeo.allocated_bytes(), 11);
+
+    let rodeo = Rodeo::with_allocator(FailingAlloc);
+    assert!(rodeo.try_alloc(42_u64).is_err());
+    assert_eq!(rodeo.allocated_bytes(), 0);
+}
+
+#[test]
+fn test_drop_all() {
+    let drops = Rc::new(Cell::new(0));
+    let counter = {
+        let drops = drops.clone();
+        DropCallback(move || drops.set(drops.get() + 1))
+    };
+
+    let mut rodeo = Rodeo::new();
+    let _ = rodeo.alloc(counter.clone());
+    let _ = rodeo.alloc_slice_fill_clone(2, &counter);
+    let _ = rodeo.alloc(42_u64);
+    let allocated = rodeo.allocated.get();
+
+    rodeo.drop_all();
+    assert_eq!(drops.get(), 3);
+    assert_eq!(rodeo.finalizers.get(), 0);
+    assert_eq!(rodeo.allocated.get(), allocated);
+    #[cfg(debug_assertions)]
+    rodeo.debug_check_invariants();
+
+    rodeo.drop_all();
+    assert_eq!(drops.get(), 3);
+
+    let _ = rodeo.alloc(counter);
+    drop(rodeo);
+    assert_eq!(drops.get(), 4);
+}
+
+#[cfg(feature = "bumpalo")]
+#[test]
+fn test_reset() {
+    let drops = Rc::new(Cell::new(0));
+    let counter = {
+        let drops = drops.clone();
+        DropCallback(move || drops.set(drops.get() + 1))
+    };
+
+    let mut rodeo = Rodeo::with_allocator(::bumpalo::Bump::with_capacity(1024));
+    let first = core::ptr::addr_of!(*rodeo.alloc(counter.clone()));
+    let _ = rodeo.alloc_slice_fill_clone(3, &counter);
+    let _ = rodeo.alloc(42_u64);
+
+    rodeo.reset();
+    assert_eq!(drops.get(), 4);
+    assert_eq!(rodeo.finalizers.get(), 0);
+    assert_eq!(rodeo.allocated.get(), 0);
+    assert!(!rodeo.undo_last());
+    #[cfg(debug_assertions)]
+    rodeo.debug_check_invariants();
+
+    // the memory is reused
+    let again = core::ptr::addr_of!(*rodeo.alloc(counter.clone()));
+    assert_eq!(first, again);
+    drop(rodeo);
+    assert_eq!(drops.get(), 5);
+
+    // without reset support, values are dropped all the same
+    let mut rodeo = Rodeo::with_allocator(crate::fallback::ChunkedAlloc::new());
+    let _ = rodeo.alloc(counter);
+    rodeo.reset();
+    assert_eq!(drops.get(), 6);
+    drop(rodeo);
+    assert_eq!(drops.get(), 6);
+}
+
+#[test]
+fn test_undo_last_chunked_alloc() {
+    use crate::fallback::{ChunkedAlloc, LeakingAlloc};
+
+    let mut rodeo = Rodeo::with_allocator(ChunkedAlloc::new());
+    let first = rodeo.alloc_slice_copy(&[1_u8, 2, 3]).as_ptr();
+    assert!(rodeo.undo_last());
+    let second = rodeo.alloc_slice_copy(&[4_u8]).as_ptr();
+    assert_eq!(first, second);
+    let _ = rodeo.alloc(String::from("undone"));
+    assert!(rodeo.undo_last());
+    assert_eq!(rodeo.finalizers.get(), 0);
+
+    let mut rodeo = Rodeo::with_allocator(LeakingAlloc);
+    let _ = rodeo.alloc(String::from("leaking"));
+    assert!(!rodeo.undo_last());
+    assert_eq!(rodeo.finalizers.get(), 1);
+    drop(rodeo);
+}
+
+#[test]
+fn test_undo_last_nested() {
+    use crate::fallback::ChunkedAlloc;
+
+    let drops = Rc::new(Cell::new(0));
+    let counter = || {
+        let drops = drops.clone();
+        DropCallback(move || drops.set(drops.get() + 1))
+    };
+
+    let mut rodeo = Rodeo::with_allocator(ChunkedAlloc::new());
+    let _ = rodeo.alloc_with(|| {
+        let _ = rodeo.alloc(counter());
+        counter()
+    });
+    // the inner allocation is the last one, but its header is not
+    assert!(!rodeo.undo_last());
+    assert_eq!(rodeo.finalizers.get(), 2);
+    #[cfg(debug_assertions)]
+    rodeo.debug_check_invariants();
+
+    let _ = rodeo.alloc(String::from("reusing"));
+    drop(rodeo);
+    assert_eq!(drops.get(), 2);
+}
+
+#[test]
+fn test_undo_last_after_empty_slice() {
+    use crate::fallback::ChunkedAlloc;
+
+    let mut rodeo = Rodeo::with_allocator(ChunkedAlloc::new());
+    let _ = rodeo.alloc(String::from("kept"));
+    let _ = rodeo.alloc_slice_copy::<u32>(&[]);
+    assert!(!rodeo.undo_last());
+    let _ = rodeo.alloc_slice_fill_copy(0, 1_u8);
+    assert!(!rodeo.undo_last());
+    let _ = rodeo.alloc_slice_concat::<u8>(&[&[], &[]]);
+    assert!(!rodeo.undo_last());
+    let _ = rodeo.alloc_slice_clone::<String>(&[]);
+    assert!(!rodeo.undo_last());
+    assert_eq!(rodeo.finalizers.get(), 1);
 }
@@ -1,3 +1,5 @@
+#![cfg(feature = "bumpalo")]
+
 use std::alloc::Layout;
 use std::sync::{Arc, Mutex};
 
@@ -25,6 +27,7 @@ fn test_tracing() {
 
     let _ = rodeo.alloc(Box::new(40_u64));
 
+    // zero-sized values without drop glue do not reach the allocator
     let _ = rodeo.alloc(());
 
     let g = layouts.lock().unwrap();
@@ -32,7 +35,6 @@ fn test_tracing() {
         &[
             Layout::new::<u32>(),
             HEADER_LAYOUT.extend(Layout::new::<Box<u64>>()).unwrap().0,
-            Layout::new::<()>(),
         ],
         g.as_slice()
     );